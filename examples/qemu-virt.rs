@@ -9,9 +9,11 @@ use serde_derive::Deserialize;
 // - `from_raw_mut`: 反序列化。
 // - `Reg`: 常见属性。其值解析方式由 `#address-cells` 和 `#size-cells` 决定。
 // - `NodeSeq`: name@... 区分的一组同级同类的连续节点，这个类型要求可变的内存。
-// - `StrSeq`: '\0' 分隔的一组字符串，设备树中一种常见的属性类型，这个类型要求可变的内存。
+// - `StrSeq`: '\0' 分隔的一组字符串，设备树中一种常见的属性类型。
+// - `Status`: 设备状态，即 `status` 属性。
+// - `Number`: 占用一个或两个单元的数值属性，如 `timebase-frequency`。
 use serde_device_tree::{
-    buildin::{Node, NodeSeq, Reg, StrSeq},
+    buildin::{Node, NodeSeq, Number, Reg, Status, StrSeq},
     error::Error,
     from_raw_mut, Dtb, DtbPtr,
 };
@@ -68,7 +70,7 @@ fn main() -> Result<(), Error> {
     #[derive(Deserialize)]
     #[serde(rename_all = "kebab-case")]
     struct Cpus<'a> {
-        timebase_frequency: u32,
+        timebase_frequency: Number,
         cpu: NodeSeq<'a>,
     }
 
@@ -77,7 +79,7 @@ fn main() -> Result<(), Error> {
     struct Cpu<'a> {
         compatible: StrSeq<'a>,
         device_type: StrSeq<'a>,
-        status: StrSeq<'a>,
+        status: Status<'a>,
         #[serde(rename = "riscv,isa")]
         isa: StrSeq<'a>,
         #[serde(rename = "mmu-type")]
@@ -122,6 +124,21 @@ fn main() -> Result<(), Error> {
         for cpu in t.cpus.cpu.iter() {
             println!("cpu@{}: {:?}", cpu.at(), cpu.deserialize::<Cpu>());
         }
+        // 只关心可用的 CPU 时，不必逐个检查 `status`
+        println!(
+            "number of enabled cpu = {}",
+            t.cpus.cpu.iter_enabled().count()
+        );
+        // 也可以直接使用内置的 CPU 模型，不必自己定义类型
+        let cpus = root.cpus().unwrap();
+        for cpu in cpus.iter_enabled() {
+            println!(
+                "hart {}: mmu = {:?}, topology = {:?}",
+                cpu.id(),
+                cpu.mmu_type(),
+                cpus.topology(&cpu)
+            );
+        }
 
         for item in t.memory.iter() {
             let mem: Memory = item.deserialize();
@@ -0,0 +1,183 @@
+use super::node::Node;
+use super::{BodyCursor, Cursor};
+use core::fmt::{Debug, Display};
+
+/// 从根节点向下的祖先节点迭代器。
+///
+/// 结构块中只记录了进入和离开节点的顺序，无法直接从节点回到父节点。
+/// 迭代器从根节点出发，每一步在当前节点的子节点中找到包含目标节点的那一个，
+/// 不需要额外的空间记录路径。
+pub(crate) struct Ancestors<'de> {
+    next: Option<Node<'de>>,
+    target: BodyCursor,
+}
+
+/// 节点的完整路径，如 `/soc/serial@10010000`。
+///
+/// 输出时从根节点向下重新查找每一级祖先节点，不需要分配内存。
+pub struct NodePath<'de>(Node<'de>);
+
+impl<'de> Node<'de> {
+    /// 获得从根节点开始、到父节点为止的祖先节点迭代器。
+    pub(crate) fn ancestors(&self) -> Ancestors<'de> {
+        Ancestors {
+            next: (self.cursor != BodyCursor::ROOT).then(|| self.root()),
+            target: self.cursor,
+        }
+    }
+
+    /// 获得父节点，根节点没有父节点。
+    pub fn parent(&self) -> Option<Node<'de>> {
+        if let Some(index) = self.dtb.borrow().index.as_ref() {
+            if let Some(i) = index.find(self.cursor) {
+                return index.parent(i).map(|i| index.node(self.dtb, i));
+            }
+        }
+        self.ancestors().last()
+    }
+
+    /// 节点的深度，根节点的深度为 0。
+    pub fn depth(&self) -> usize {
+        self.ancestors().count()
+    }
+
+    /// 节点名，包括 `@` 后的单元地址。根节点的名字为空。
+    pub fn name(&self) -> &'de str {
+        self.cursor.title_on(self.dtb).split_on(self.dtb).0
+    }
+
+    /// 获得节点的完整路径。
+    pub fn path(&self) -> NodePath<'de> {
+        NodePath(self.clone())
+    }
+}
+
+impl Display for NodePath<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.0.cursor == BodyCursor::ROOT {
+            return write!(f, "/");
+        }
+        for node in self.0.ancestors().skip(1) {
+            write!(f, "/{}", node.name())?;
+        }
+        write!(f, "/{}", self.0.name())
+    }
+}
+
+impl PartialEq<str> for NodePath<'_> {
+    /// 逐段比较路径，不需要分配内存。
+    fn eq(&self, other: &str) -> bool {
+        struct Matcher<'a>(&'a str);
+        impl core::fmt::Write for Matcher<'_> {
+            fn write_str(&mut self, s: &str) -> core::fmt::Result {
+                self.0 = self.0.strip_prefix(s).ok_or(core::fmt::Error)?;
+                Ok(())
+            }
+        }
+        let mut matcher = Matcher(other);
+        core::fmt::write(&mut matcher, format_args!("{self}")).is_ok() && matcher.0.is_empty()
+    }
+}
+
+impl PartialEq<&str> for NodePath<'_> {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+impl Debug for NodePath<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "\"{self}\"")
+    }
+}
+
+impl<'de> Iterator for Ancestors<'de> {
+    type Item = Node<'de>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next.take()?;
+        let dtb = current.dtb;
+        if let Some(index) = dtb.borrow().index.as_ref() {
+            if let Some(i) = index.find(current.cursor) {
+                let target = self.target;
+                self.next = index.children(i).find_map(|j| {
+                    let child = index.node(dtb, j);
+                    (child.cursor < target && target < index.end(j)).then_some(child)
+                });
+                return Some(current);
+            }
+        }
+        let mut cursor = current.nodes_start;
+        while let Some(Cursor::Title(c)) = cursor.as_mut().map(|cursor| cursor.move_on(dtb)) {
+            let (name, body) = c.split_on(dtb);
+            // 子节点就是目标节点，当前节点是最后一个祖先
+            if body == self.target {
+                break;
+            }
+            let node_cursor = c.take_node_on(dtb, name);
+            if body < self.target && self.target < node_cursor.next_cursor {
+                self.next = Some(Node::from_cursor(dtb, current.reg, body));
+                break;
+            }
+            cursor = Some(node_cursor.next_cursor);
+        }
+        Some(current)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{buildin::Node, from_raw_mut, Dtb, DtbPtr};
+    use serde_derive::Deserialize;
+
+    const RAW_DEVICE_TREE: &[u8] = include_bytes!("../../examples/hifive-unmatched-a00.dtb");
+    const BUFFER_SIZE: usize = RAW_DEVICE_TREE.len();
+    #[repr(align(8))]
+    struct AlignedBuffer {
+        pub data: [u8; RAW_DEVICE_TREE.len()],
+    }
+    #[derive(Deserialize)]
+    struct Tree<'a> {
+        soc: Node<'a>,
+    }
+    #[test]
+    fn test_parent_path() {
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE],
+        });
+        aligned_data.data[..BUFFER_SIZE].clone_from_slice(RAW_DEVICE_TREE);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        assert!(node.parent().is_none());
+        assert_eq!(node.depth(), 0);
+        assert_eq!(node.name(), "");
+        assert_eq!(format!("{}", node.path()), "/");
+
+        let phy = node.find("/soc/ethernet@10090000/ethernet-phy@0").unwrap();
+        assert_eq!(phy.name(), "ethernet-phy@0");
+        assert_eq!(phy.depth(), 3);
+        assert_eq!(
+            format!("{}", phy.path()),
+            "/soc/ethernet@10090000/ethernet-phy@0"
+        );
+        let ethernet = phy.parent().unwrap();
+        assert_eq!(
+            format!("{:?}", ethernet.path()),
+            "\"/soc/ethernet@10090000\""
+        );
+        assert_eq!(ethernet.parent().unwrap().name(), "soc");
+        assert!(ethernet.path() == "/soc/ethernet@10090000");
+        assert!(ethernet.path() != "/soc/ethernet@10090000/");
+        assert!(ethernet.path() != "/soc/ethernet");
+        assert!(node.path() == "/");
+        assert_eq!(ethernet.parent().unwrap().parent().unwrap().depth(), 0);
+
+        // 结构体字段中的节点
+        let t: Tree = node.deserialize();
+        assert_eq!(format!("{}", t.soc.path()), "/soc");
+        assert_eq!(t.soc.nodes().count(), 17);
+    }
+}
@@ -0,0 +1,237 @@
+use super::node::Node;
+use super::{ValueCursor, ValueDeserializer};
+use core::fmt::{Debug, Display};
+use serde::{de, Deserialize};
+
+/// 不透明的字节串属性值。
+///
+/// 调试输出采用设备树源码中的字节串写法，如 `[00 11 22]`。
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Bytes<'de>(&'de [u8]);
+
+/// 固定长度的字节串属性值。
+///
+/// 反序列化时检查属性值的长度恰好为 `N` 字节。
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct FixedBytes<const N: usize>(pub [u8; N]);
+
+/// 以太网 MAC 地址，对应 `mac-address` 和 `local-mac-address` 属性。
+///
+/// 反序列化时检查属性值的长度恰好为 6 字节，显示为 `00:11:22:33:44:55` 的形式。
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MacAddress(pub [u8; 6]);
+
+impl<'de> Bytes<'de> {
+    /// 属性值的原始数据。
+    pub const fn as_bytes(&self) -> &'de [u8] {
+        self.0
+    }
+
+    /// 字节数。
+    pub const fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// 如果属性值为空，返回 `true`。
+    pub const fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<const N: usize> FixedBytes<N> {
+    /// 从属性值构造，长度不是 `N` 时返回 `None`。
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        data.try_into().ok().map(Self)
+    }
+}
+
+impl MacAddress {
+    /// 从属性值构造，长度不是 6 时返回 `None`。
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        data.try_into().ok().map(Self)
+    }
+
+    /// 地址的 6 个字节。
+    pub const fn octets(&self) -> [u8; 6] {
+        self.0
+    }
+
+    /// 如果是组播地址（包括广播地址），返回 `true`。
+    pub const fn is_multicast(&self) -> bool {
+        self.0[0] & 1 != 0
+    }
+
+    /// 如果可以用作设备地址，返回 `true`。
+    ///
+    /// 全零地址和组播地址不能用作设备地址；固件常用全零的 `local-mac-address` 表示未分配地址。
+    pub fn is_valid(&self) -> bool {
+        self.0 != [0; 6] && !self.is_multicast()
+    }
+}
+
+impl<'de> Node<'de> {
+    /// 获得设备的 MAC 地址。
+    ///
+    /// 依次尝试 `mac-address` 和 `local-mac-address` 属性，返回第一个可用的地址。
+    pub fn mac_address(&self) -> Option<MacAddress> {
+        ["mac-address", "local-mac-address"]
+            .into_iter()
+            .filter_map(|name| self.get_prop(name))
+            .filter_map(|prop| MacAddress::from_bytes(prop.deserialize::<&[u8]>()))
+            .find(MacAddress::is_valid)
+    }
+}
+
+/// 按设备树源码的字节串写法输出。
+fn write_bytes(f: &mut core::fmt::Formatter<'_>, data: &[u8]) -> core::fmt::Result {
+    write!(f, "[")?;
+    for (i, byte) in data.iter().enumerate() {
+        if i != 0 {
+            write!(f, " ")?;
+        }
+        write!(f, "{byte:02x}")?;
+    }
+    write!(f, "]")
+}
+
+impl Debug for Bytes<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write_bytes(f, self.0)
+    }
+}
+
+impl Display for Bytes<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write_bytes(f, self.0)
+    }
+}
+
+impl<const N: usize> Debug for FixedBytes<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write_bytes(f, &self.0)
+    }
+}
+
+impl<const N: usize> Display for FixedBytes<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write_bytes(f, &self.0)
+    }
+}
+
+impl Debug for MacAddress {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl Display for MacAddress {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let [a, b, c, d, e, g] = self.0;
+        write!(f, "{a:02x}:{b:02x}:{c:02x}:{d:02x}:{e:02x}:{g:02x}")
+    }
+}
+
+impl<'de> Deserialize<'de> for Bytes<'_> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value_deserialzer = ValueDeserializer::deserialize(deserializer)?;
+        match value_deserialzer.cursor {
+            ValueCursor::Prop(_, cursor) => Ok(Self(cursor.data_on(value_deserialzer.dtb))),
+            _ => {
+                unreachable!("Bytes Deserialize should only be called by prop cursor")
+            }
+        }
+    }
+}
+
+impl<'de, const N: usize> Deserialize<'de> for FixedBytes<N> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let data = Bytes::deserialize(deserializer)?.0;
+        Self::from_bytes(data).ok_or_else(|| de::Error::invalid_length(data.len(), &Expected(N)))
+    }
+}
+
+impl<'de> Deserialize<'de> for MacAddress {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        FixedBytes::<6>::deserialize(deserializer).map(|bytes| Self(bytes.0))
+    }
+}
+
+/// 期望的字节数，用于长度错误信息。
+struct Expected(usize);
+
+impl de::Expected for Expected {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} bytes", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buildin::{Bytes, FixedBytes, MacAddress, Node};
+    use crate::{from_raw_mut, Dtb, DtbPtr};
+    use serde_derive::Deserialize;
+
+    const RAW_DEVICE_TREE: &[u8] = include_bytes!("../../examples/hifive-unmatched-a00.dtb");
+    const BUFFER_SIZE: usize = RAW_DEVICE_TREE.len();
+    #[repr(align(8))]
+    struct AlignedBuffer {
+        pub data: [u8; RAW_DEVICE_TREE.len()],
+    }
+    #[derive(Deserialize)]
+    struct Ethernet<'a> {
+        #[serde(rename = "local-mac-address")]
+        local_mac_address: MacAddress,
+        #[serde(rename = "phy-mode")]
+        phy_mode: Bytes<'a>,
+        #[serde(rename = "reg")]
+        reg: FixedBytes<32>,
+    }
+    #[derive(Deserialize)]
+    struct WrongLength {
+        #[serde(rename = "compatible")]
+        _compatible: MacAddress,
+    }
+    #[test]
+    fn test_bytes() {
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE],
+        });
+        aligned_data.data[..BUFFER_SIZE].clone_from_slice(RAW_DEVICE_TREE);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        let ethernet = node.find("/soc/ethernet@10090000").unwrap();
+        let t: Ethernet = ethernet.deserialize();
+        assert_eq!(t.local_mac_address, MacAddress([0; 6]));
+        assert_eq!(format!("{}", t.local_mac_address), "00:00:00:00:00:00");
+        assert!(!t.local_mac_address.is_valid());
+        assert_eq!(t.phy_mode.as_bytes(), b"gmii\0");
+        assert_eq!(format!("{:?}", t.phy_mode), "[67 6d 69 69 00]");
+        assert_eq!(t.reg.0[4..8], [0x10, 0x09, 0x00, 0x00]);
+        // 全零地址不可用
+        assert_eq!(ethernet.mac_address(), None);
+
+        // 长度不符
+        assert!(from_raw_mut::<WrongLength>(&dtb).is_err());
+
+        let mac = MacAddress::from_bytes(&[0x02, 0x00, 0x5e, 0x10, 0x20, 0x30]).unwrap();
+        assert_eq!(format!("{mac:?}"), "02:00:5e:10:20:30");
+        assert!(mac.is_valid());
+        assert!(MacAddress::from_bytes(&[0; 5]).is_none());
+        assert_eq!(
+            format!("{}", FixedBytes::<2>::from_bytes(&[0xab, 0x01]).unwrap()),
+            "[ab 01]"
+        );
+    }
+}
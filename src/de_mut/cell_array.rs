@@ -0,0 +1,199 @@
+use super::{PropCursor, RefDtb, ValueCursor, BLOCK_LEN};
+use crate::error::Error as DtError;
+use core::fmt::Debug;
+use serde::Deserialize;
+
+/// 由定长单元组构成的属性值。
+///
+/// `reg`、`ranges`、`bus-range` 等属性都由若干项组成，
+/// 每项又分为若干组，各组分别占用若干个 32 位单元。
+/// 例如 `ranges` 的一项依次是子地址、父地址和长度，各组单元数由总线的
+/// `#address-cells` 和 `#size-cells` 决定，可能是 `[2, 2, 1]`。
+///
+/// [`CellArray::entries`] 按给定的各组单元数检查属性长度，并返回逐项解析的迭代器，
+/// 每项解析为一个 `[u64; N]`。
+#[derive(Clone, Copy)]
+pub struct CellArray<'de> {
+    data: &'de [u8],
+    file_index: usize,
+}
+
+/// 单元组迭代器。
+///
+/// 每组单元拼接为一个 `u64`，超过两个单元的组只保留低 64 位。
+#[derive(Clone)]
+pub struct CellArrayIter<'de, const N: usize> {
+    data: &'de [u8],
+    widths: [usize; N],
+}
+
+impl<'de> Deserialize<'de> for CellArray<'_> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value_deserialzer = super::ValueDeserializer::deserialize(deserializer)?;
+        let cursor = match value_deserialzer.cursor {
+            ValueCursor::Prop(_, cursor) => cursor,
+            _ => {
+                unreachable!("CellArray Deserialize should only be called by prop cursor")
+            }
+        };
+        Ok(Self::from_cursor(value_deserialzer.dtb, cursor))
+    }
+}
+
+impl<'de> CellArray<'de> {
+    pub(super) fn from_cursor(dtb: RefDtb<'de>, cursor: PropCursor) -> Self {
+        Self {
+            data: cursor.data_on(dtb),
+            file_index: cursor.file_index_on(dtb),
+        }
+    }
+
+    /// 从属性值构造。
+    pub const fn new(data: &'de [u8]) -> Self {
+        Self {
+            data,
+            file_index: 0,
+        }
+    }
+
+    /// 属性值的原始数据。
+    pub const fn as_bytes(&self) -> &'de [u8] {
+        self.data
+    }
+
+    /// 属性值包含的单元数。
+    pub const fn cell_count(&self) -> usize {
+        self.data.len() / BLOCK_LEN
+    }
+
+    /// 按每项各组的单元数 `widths` 解析属性值。
+    ///
+    /// 属性值的长度必须是一项长度的整数倍，否则返回错误。
+    pub fn entries<const N: usize>(
+        &self,
+        widths: [usize; N],
+    ) -> Result<CellArrayIter<'de, N>, DtError> {
+        let entry_len = widths.iter().sum::<usize>() * BLOCK_LEN;
+        let remaining = match self.data.len().checked_rem(entry_len) {
+            Some(remaining) => remaining,
+            None => self.data.len(),
+        };
+        if remaining != 0 {
+            return Err(DtError::slice_eof_unpexpected(
+                entry_len as _,
+                remaining as _,
+                self.file_index,
+            ));
+        }
+        Ok(CellArrayIter::new(self.data, widths))
+    }
+}
+
+impl<'de, const N: usize> CellArrayIter<'de, N> {
+    /// 不检查长度，构造迭代器；末尾不足一项的数据被忽略。
+    pub(super) const fn new(data: &'de [u8], widths: [usize; N]) -> Self {
+        Self { data, widths }
+    }
+
+    #[inline]
+    fn entry_len(&self) -> usize {
+        self.widths.iter().sum::<usize>() * BLOCK_LEN
+    }
+}
+
+impl<const N: usize> Iterator for CellArrayIter<'_, N> {
+    type Item = [u64; N];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry_len = self.entry_len();
+        if entry_len == 0 || self.data.len() < entry_len {
+            return None;
+        }
+        let mut entry = [0; N];
+        for (value, width) in entry.iter_mut().zip(self.widths) {
+            let (group, data) = self.data.split_at(width * BLOCK_LEN);
+            self.data = data;
+            *value = group.chunks_exact(BLOCK_LEN).fold(0u64, |acc, cell| {
+                (acc << 32) | u32::from_be_bytes(cell.try_into().unwrap()) as u64
+            });
+        }
+        Some(entry)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.data.len().checked_div(self.entry_len()).unwrap_or(0);
+        (len, Some(len))
+    }
+}
+
+impl<const N: usize> ExactSizeIterator for CellArrayIter<'_, N> {}
+
+impl Debug for CellArray<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "<")?;
+        for (i, cell) in CellArrayIter::new(self.data, [1]).enumerate() {
+            if i != 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{:#x}", cell[0])?;
+        }
+        write!(f, ">")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buildin::{CellArray, Node};
+    use crate::{from_raw_mut, Dtb, DtbPtr};
+
+    const RAW_DEVICE_TREE: &[u8] = include_bytes!("../../examples/qemu-virt.dtb");
+    const BUFFER_SIZE: usize = RAW_DEVICE_TREE.len();
+    #[test]
+    fn test_cell_array() {
+        #[repr(align(8))]
+        struct AlignedBuffer {
+            pub data: [u8; RAW_DEVICE_TREE.len()],
+        }
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE],
+        });
+        aligned_data.data[..BUFFER_SIZE].clone_from_slice(RAW_DEVICE_TREE);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        let pci = node.find("/soc/pci@30000000").unwrap();
+        let bus_range = pci
+            .get_prop("bus-range")
+            .unwrap()
+            .deserialize::<CellArray>();
+        assert_eq!(bus_range.cell_count(), 2);
+        assert_eq!(bus_range.entries([1, 1]).unwrap().next(), Some([0, 0xff]));
+
+        // PCI 子地址有 3 个单元，只保留低 64 位
+        let ranges = pci.get_prop("ranges").unwrap().deserialize::<CellArray>();
+        let mut entries = ranges.entries([3, 2, 2]).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries.next(), Some([0x0, 0x300_0000, 0x1_0000]));
+        assert_eq!(
+            entries.next(),
+            Some([0x4000_0000, 0x4000_0000, 0x4000_0000])
+        );
+        assert_eq!(
+            entries.next(),
+            Some([0x4_0000_0000, 0x4_0000_0000, 0x4_0000_0000])
+        );
+        assert_eq!(entries.next(), None);
+        // 长度不是整数项
+        assert!(ranges.entries([2, 2]).is_err());
+        assert!(ranges.entries([]).is_err());
+
+        let empty = CellArray::new(&[]);
+        assert_eq!(empty.entries([]).unwrap().count(), 0);
+        assert_eq!(empty.entries([2, 1]).unwrap().count(), 0);
+    }
+}
@@ -1,7 +1,7 @@
 ﻿use super::{DtError, RefDtb, StructureBlock, BLOCK_LEN};
 use core::marker::PhantomData;
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 #[repr(C)]
 pub(super) struct AnyCursor<T: Type = Body>(usize, PhantomData<T>);
 
@@ -11,11 +11,11 @@ pub(super) type PropCursor = AnyCursor<Prop>;
 
 pub(super) trait Type {}
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub(super) struct Body {}
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub(super) struct Title {}
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub(super) struct Prop {}
 
 impl Type for Body {}
@@ -43,6 +43,11 @@ impl<T: Type> AnyCursor<T> {
         self.0 += len;
     }
 
+    /// 光标所在的结构块序号。
+    pub const fn block(&self) -> usize {
+        self.0
+    }
+
     /// 光标相对文件头的偏移。
     pub fn file_index_on(&self, dtb: RefDtb) -> usize {
         self.0 * BLOCK_LEN + dtb.borrow().off_dt_struct()
@@ -52,6 +57,11 @@ impl<T: Type> AnyCursor<T> {
 impl BodyCursor {
     pub const ROOT: Self = Self(2, PhantomData);
 
+    /// 构造位于第 `block` 个结构块的光标。
+    pub const fn at(block: usize) -> Self {
+        Self(block, PhantomData)
+    }
+
     /// 移动到下一个项目。
     pub fn move_on(&mut self, dtb: RefDtb) -> Cursor {
         use StructureBlock as B;
@@ -116,6 +126,18 @@ impl BodyCursor {
         }
     }
 
+    /// 找到节点体所属节点的标题。
+    ///
+    /// 节点名中不会出现 `FDT_BEGIN_NODE` 块，因此向前找到的第一个即是节点的开始。
+    pub fn title_on(&self, dtb: RefDtb) -> TitleCursor {
+        let structure = &dtb.borrow().structure;
+        let mut index = self.0 - 1;
+        while structure[index] != StructureBlock::NODE_BEGIN {
+            index -= 1;
+        }
+        AnyCursor(index, PhantomData)
+    }
+
     /// 离开当前子树。
     pub fn escape_from(&mut self, dtb: RefDtb) {
         let mut level = 1;
@@ -0,0 +1,226 @@
+use super::cursor::MoveResult;
+use super::node::Node;
+use super::{BodyCursor, RefDtb, RegConfig};
+
+/// 记录地址空间格式的最大深度，更深的节点从父节点重新查找格式。
+const REG_STACK_DEPTH: usize = 16;
+
+/// 深度优先的子孙节点迭代器。
+///
+/// 迭代器直接在结构块上移动光标，不递归，占用的栈空间是固定的。
+/// 每一项是节点相对起始节点的深度和节点本身，起始节点自己的深度为 0。
+/// 调用 [`Descendants::skip_subtree`] 可以跳过上一次返回的节点的所有子孙节点。
+///
+/// 迭代器只记录 16 层以内的地址空间格式。
+/// 更深的节点仍能正确解析 `reg`，但每个节点都要从根节点向下查找一次父节点，会慢得多。
+pub struct Descendants<'de> {
+    dtb: RefDtb<'de>,
+    cursor: Option<BodyCursor>,
+    depth: usize,
+    regs: [RegConfig; REG_STACK_DEPTH],
+    first: Option<Node<'de>>,
+    /// 光标仍在上一次返回的节点内部。
+    inside: bool,
+}
+
+impl<'de> Node<'de> {
+    /// 获得包括自己在内的所有子孙节点的深度优先迭代器。
+    pub fn descendants(&self) -> Descendants<'de> {
+        let mut regs = [RegConfig::DEFAULT; REG_STACK_DEPTH];
+        regs[0] = self.reg;
+        Descendants {
+            dtb: self.dtb,
+            cursor: Some(self.cursor),
+            depth: 0,
+            regs,
+            first: Some(self.clone()),
+            inside: false,
+        }
+    }
+}
+
+impl Descendants<'_> {
+    /// 跳过上一次返回的节点的所有子孙节点。
+    ///
+    /// 如果上一次返回的是起始节点，迭代结束。
+    pub fn skip_subtree(&mut self) {
+        if !core::mem::take(&mut self.inside) {
+            return;
+        }
+        let Some(cursor) = self.cursor.as_mut() else {
+            return;
+        };
+        if self.depth == 0 {
+            self.cursor = None;
+            return;
+        }
+        cursor.escape_from(self.dtb);
+        self.depth -= 1;
+    }
+}
+
+impl<'de> Iterator for Descendants<'de> {
+    type Item = (usize, Node<'de>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(node) = self.first.take() {
+            self.inside = true;
+            return Some((0, node));
+        }
+        self.inside = false;
+        let cursor = self.cursor.as_mut()?;
+        loop {
+            match cursor.move_next(self.dtb) {
+                // 进入一个子节点，光标已经位于节点名之后
+                MoveResult::In => {
+                    let node = match self.regs.get(self.depth) {
+                        Some(reg) => Node::from_cursor(self.dtb, *reg, *cursor),
+                        // 超出记录的深度，地址空间格式从父节点继承
+                        None => {
+                            let node = Node::from_cursor(self.dtb, RegConfig::DEFAULT, *cursor);
+                            match node.parent() {
+                                Some(parent) => Node::from_cursor(self.dtb, parent.reg, *cursor),
+                                None => node,
+                            }
+                        }
+                    };
+                    self.depth += 1;
+                    if self.depth < REG_STACK_DEPTH {
+                        self.regs[self.depth] = node.reg;
+                    }
+                    self.inside = true;
+                    return Some((self.depth, node));
+                }
+                MoveResult::Out => {
+                    // 离开起始节点，遍历结束
+                    if self.depth == 0 {
+                        self.cursor = None;
+                        return None;
+                    }
+                    self.depth -= 1;
+                }
+                MoveResult::Others => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BodyCursor, RegConfig};
+    use crate::{
+        buildin::{Node, Reg},
+        from_raw_mut, Dtb, DtbPtr,
+    };
+
+    const RAW_DEVICE_TREE: &[u8] = include_bytes!("../../examples/hifive-unmatched-a00.dtb");
+    const BUFFER_SIZE: usize = RAW_DEVICE_TREE.len();
+    const RAW_DEVICE_TREE_DEEP: &[u8] = include_bytes!("../../examples/deep.dtb");
+    const BUFFER_SIZE_DEEP: usize = RAW_DEVICE_TREE_DEEP.len();
+    const RAW_DEVICE_TREE_MALFORMED: &[u8] = include_bytes!("../../examples/malformed-cells.dtb");
+    const BUFFER_SIZE_MALFORMED: usize = RAW_DEVICE_TREE_MALFORMED.len();
+    #[test]
+    fn test_descendants() {
+        #[repr(align(8))]
+        struct AlignedBuffer {
+            pub data: [u8; RAW_DEVICE_TREE.len()],
+        }
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE],
+        });
+        aligned_data.data[..BUFFER_SIZE].clone_from_slice(RAW_DEVICE_TREE);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        assert_eq!(node.descendants().count(), 70);
+        for (depth, node) in node.descendants() {
+            assert_eq!(node.depth(), depth);
+        }
+
+        // 只遍历根节点和它的子节点
+        let mut iter = node.descendants();
+        let mut count = 0;
+        while let Some((depth, _)) = iter.next() {
+            if depth == 1 {
+                iter.skip_subtree();
+                // 连续调用不会跳出父节点
+                iter.skip_subtree();
+            }
+            count += 1;
+        }
+        assert_eq!(count, node.nodes().count() + 1);
+
+        // 跳过起始节点的子树
+        let soc = node.find("/soc").unwrap();
+        let mut iter = soc.descendants();
+        assert_eq!(iter.next().unwrap().0, 0);
+        iter.skip_subtree();
+        assert!(iter.next().is_none());
+
+        // 只跳过 /cpus 的子树
+        let cpus = node.find("/cpus").unwrap();
+        let mut iter = node.descendants();
+        let mut count = 0;
+        while let Some((_, node)) = iter.next() {
+            if node.name() == "cpus" {
+                iter.skip_subtree();
+            }
+            count += 1;
+        }
+        assert_eq!(count, 70 - (cpus.descendants().count() - 1));
+    }
+    #[test]
+    fn test_descendants_deep() {
+        #[repr(align(8))]
+        struct AlignedBuffer {
+            pub data: [u8; RAW_DEVICE_TREE_DEEP.len()],
+        }
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE_DEEP],
+        });
+        aligned_data.data[..BUFFER_SIZE_DEEP].clone_from_slice(RAW_DEVICE_TREE_DEEP);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        let (depth, leaf) = node.descendants().last().unwrap();
+        assert_eq!(depth, 21);
+        assert_eq!(leaf.name(), "leaf@100000000");
+        // 超出记录深度的节点仍按父节点的格式解析 `reg`
+        let reg = leaf.get_prop("reg").unwrap().deserialize::<Reg>();
+        let mut regions = reg.iter();
+        assert_eq!(regions.next().unwrap().0, 0x1_0000_0000..0x1_0000_1000);
+        assert!(regions.next().is_none());
+    }
+    #[test]
+    fn test_descendants_malformed_cells() {
+        #[repr(align(8))]
+        struct AlignedBuffer {
+            pub data: [u8; RAW_DEVICE_TREE_MALFORMED.len()],
+        }
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE_MALFORMED],
+        });
+        aligned_data.data[..BUFFER_SIZE_MALFORMED].clone_from_slice(RAW_DEVICE_TREE_MALFORMED);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        // 反序列化会报告错误，但直接构造的节点仍然可用
+        assert!(from_raw_mut::<Node>(&dtb).is_err());
+        let node = Node::from_cursor(&dtb, RegConfig::DEFAULT, BodyCursor::ROOT);
+        // 根节点的 `#size-cells` 不是 4 字节，忽略它并沿用默认格式
+        assert_eq!(node.reg.address_cells, 2);
+        assert_eq!(node.reg.size_cells, 1);
+        assert_eq!(node.descendants().count(), 4);
+        let memory = node.find("/memory@80000000").unwrap();
+        let reg = memory.get_prop("reg").unwrap().deserialize::<Reg>();
+        assert_eq!(reg.iter().next().unwrap().0, 0x8000_0000..0x8000_1000);
+        let serial = node.find("/soc/serial@1000").unwrap();
+        assert_eq!(serial.parent().unwrap().parent().unwrap().path(), "/");
+        assert_eq!(node.find_compatible(&["test,malformed-cells"]).count(), 1);
+    }
+}
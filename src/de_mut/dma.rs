@@ -0,0 +1,112 @@
+use super::node::Node;
+use super::translate::RangesDirection;
+
+impl Node<'_> {
+    /// 将 CPU 视角的物理地址转换为本设备发起 DMA 时使用的总线地址。
+    ///
+    /// 从根节点向下，依次按每一级总线的 `dma-ranges` 属性做反向映射。
+    /// 没有 `dma-ranges` 或 `dma-ranges` 为空的总线视为一一映射；
+    /// 某一级总线无法访问该地址时，返回 `None`。
+    pub fn dma_translate(&self, cpu_addr: u64) -> Option<u64> {
+        let mut addr = cpu_addr;
+        let mut parent: Option<Node> = None;
+        for bus in self.ancestors() {
+            // 根节点没有父地址空间，其 dma-ranges 没有意义
+            // 没有 dma-ranges 的总线视为一一映射
+            if let Some(parent) = parent.filter(|_| bus.get_prop("dma-ranges").is_some()) {
+                addr = bus.map_ranges(
+                    "dma-ranges",
+                    RangesDirection::ToChild,
+                    parent.reg.address_cells,
+                    addr,
+                )?;
+            }
+            parent = Some(bus);
+        }
+        Some(addr)
+    }
+
+    /// 设备的 DMA 访问是否与 CPU 缓存一致。
+    ///
+    /// 取本节点及祖先节点中最近的 `dma-coherent` 或 `dma-noncoherent` 属性，都没有时认为不一致。
+    pub fn is_dma_coherent(&self) -> bool {
+        let mut coherent = false;
+        for node in self.ancestors().chain(core::iter::once(self.clone())) {
+            for prop in node.props() {
+                match prop.get_name() {
+                    "dma-coherent" => coherent = true,
+                    "dma-noncoherent" => coherent = false,
+                    _ => {}
+                }
+            }
+        }
+        coherent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{buildin::Node, from_raw_mut, Dtb, DtbPtr};
+
+    const RAW_DEVICE_TREE: &[u8] = include_bytes!("../../examples/dma-ranges.dtb");
+    const BUFFER_SIZE: usize = RAW_DEVICE_TREE.len();
+    const RAW_DEVICE_TREE_QEMU: &[u8] = include_bytes!("../../examples/qemu-virt.dtb");
+    const BUFFER_SIZE_QEMU: usize = RAW_DEVICE_TREE_QEMU.len();
+    #[test]
+    fn test_dma_translate() {
+        #[repr(align(8))]
+        struct AlignedBuffer {
+            pub data: [u8; RAW_DEVICE_TREE.len()],
+        }
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE],
+        });
+        aligned_data.data[..BUFFER_SIZE].clone_from_slice(RAW_DEVICE_TREE);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        let dma = node.find("/soc/dma@1000").unwrap();
+        assert_eq!(dma.dma_translate(0x8000_1000), Some(0x1000));
+        assert_eq!(dma.dma_translate(0x1000), None);
+        assert!(dma.is_dma_coherent());
+        // 两级映射，且下层总线覆盖了一致性
+        let eth = node.find("/soc/bridge@10000000/ethernet@2000").unwrap();
+        assert_eq!(eth.dma_translate(0x8000_1000), Some(0x1000_1000));
+        assert_eq!(eth.dma_translate(0x9000_0000), None);
+        assert!(!eth.is_dma_coherent());
+        // 空 dma-ranges 表示一一映射
+        let uart = node.find("/soc/bus@20000000/serial@3000").unwrap();
+        assert_eq!(uart.dma_translate(0x8000_1000), Some(0x1000));
+        assert!(uart.is_dma_coherent());
+        // 根节点下的设备不需要转换
+        assert_eq!(
+            node.find("/sram@0").unwrap().dma_translate(0x1000),
+            Some(0x1000)
+        );
+        assert!(!node.is_dma_coherent());
+    }
+
+    #[test]
+    fn test_dma_coherent() {
+        #[repr(align(8))]
+        struct AlignedBuffer {
+            pub data: [u8; RAW_DEVICE_TREE_QEMU.len()],
+        }
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE_QEMU],
+        });
+        aligned_data.data[..BUFFER_SIZE_QEMU].clone_from_slice(RAW_DEVICE_TREE_QEMU);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        assert!(node.find("/fw-cfg@10100000").unwrap().is_dma_coherent());
+        assert!(node.find("/soc/pci@30000000").unwrap().is_dma_coherent());
+        let rtc = node.find("/soc/rtc@101000").unwrap();
+        assert!(!rtc.is_dma_coherent());
+        assert_eq!(rtc.dma_translate(0x8000_0000), Some(0x8000_0000));
+    }
+}
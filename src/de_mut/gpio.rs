@@ -0,0 +1,169 @@
+use super::node::Node;
+use super::phandle::{map_nexus, PhandleArgs, PhandleArgsIter, Specifier};
+use core::fmt::Debug;
+
+/// GPIO 列表，对应 `*-gpios` 或 `*-gpio` 属性。
+///
+/// `reset-gpios = <&gpio 8 GPIO_ACTIVE_LOW>;` 这样的属性由若干项组成，
+/// 每项是 GPIO 控制器的 phandle，后接控制器 `#gpio-cells` 指定数量的参数。
+/// 如果 phandle 指向连接器一类的 nexus 节点，
+/// 将按 `gpio-map`、`gpio-map-mask` 和 `gpio-map-pass-thru` 属性映射到真正的控制器上。
+pub struct Gpios<'de>(PhandleArgs<'de>);
+
+/// GPIO 列表迭代器。
+///
+/// 遇到无法解析或映射的项时，迭代结束。
+pub struct GpiosIter<'de, 'b>(PhandleArgsIter<'de, 'b>);
+
+/// 一个 GPIO。
+pub struct Gpio<'de> {
+    controller: Node<'de>,
+    spec: Specifier,
+}
+
+impl<'de> Node<'de> {
+    /// 获得名为 `<con_id>-gpios` 或 `<con_id>-gpio` 的 GPIO 列表。
+    ///
+    /// `con_id` 为空字符串时，对应 `gpios` 或 `gpio` 属性。
+    pub fn gpios(&self, con_id: &str) -> Option<Gpios<'de>> {
+        let prop = self.props().find(|prop| {
+            let name = prop.get_name();
+            match name
+                .strip_suffix("gpios")
+                .or_else(|| name.strip_suffix("gpio"))
+            {
+                Some("") => con_id.is_empty(),
+                Some(stem) => stem.strip_suffix('-') == Some(con_id),
+                None => false,
+            }
+        })?;
+        self.phandle_args(prop.get_name(), "#gpio-cells").map(Gpios)
+    }
+}
+
+impl<'de> Gpios<'de> {
+    /// 构造一个依次访问每个 GPIO 的迭代器。
+    pub fn iter<'b>(&'b self) -> GpiosIter<'de, 'b> {
+        GpiosIter(self.0.iter())
+    }
+}
+
+impl Debug for Gpios<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'de> Iterator for GpiosIter<'de, '_> {
+    type Item = Gpio<'de>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let arg = self.0.next()?;
+        let (controller, spec) = map_nexus(arg.provider().clone(), arg.specifier()?, "gpio")?;
+        Some(Gpio { controller, spec })
+    }
+}
+
+impl<'de> Gpio<'de> {
+    /// GPIO 控制器节点。
+    pub fn controller(&self) -> &Node<'de> {
+        &self.controller
+    }
+
+    /// 控制器上的引脚编号，即说明符的第一个单元。
+    pub fn line(&self) -> u32 {
+        self.spec.get(0).unwrap_or(0)
+    }
+
+    /// GPIO 标志，即说明符的第二个单元，如 `GPIO_ACTIVE_LOW`。
+    pub fn flags(&self) -> u32 {
+        self.spec.get(1).unwrap_or(0)
+    }
+
+    /// 映射后的完整说明符。
+    pub fn cells(&self) -> &[u32] {
+        self.spec.as_slice()
+    }
+}
+
+impl Debug for Gpio<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "<")?;
+        if let Some(phandle) = self.controller.phandle() {
+            write!(f, "{phandle:#x}")?;
+        }
+        for cell in self.cells() {
+            write!(f, " {cell:#x}")?;
+        }
+        write!(f, ">")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{buildin::Node, from_raw_mut, Dtb, DtbPtr};
+
+    const RAW_DEVICE_TREE: &[u8] = include_bytes!("../../examples/gpio-nexus.dtb");
+    const BUFFER_SIZE: usize = RAW_DEVICE_TREE.len();
+    const RAW_DEVICE_TREE_HIFIVE: &[u8] = include_bytes!("../../examples/hifive-unmatched-a00.dtb");
+    const BUFFER_SIZE_HIFIVE: usize = RAW_DEVICE_TREE_HIFIVE.len();
+    #[test]
+    fn test_gpio_nexus() {
+        #[repr(align(8))]
+        struct AlignedBuffer {
+            pub data: [u8; RAW_DEVICE_TREE.len()],
+        }
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE],
+        });
+        aligned_data.data[..BUFFER_SIZE].clone_from_slice(RAW_DEVICE_TREE);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        let led = node.find("/led").unwrap();
+        let gpios = led.gpios("").unwrap();
+        let gpios = gpios.iter().collect::<Vec<_>>();
+        assert_eq!(gpios.len(), 2);
+        // 经由连接器映射，标志位透传
+        assert_eq!(gpios[0].controller().phandle(), Some(1));
+        assert_eq!((gpios[0].line(), gpios[0].flags()), (13, 1));
+        assert_eq!((gpios[1].line(), gpios[1].flags()), (5, 0));
+
+        let expansion = node.find("/expansion").unwrap();
+        let reset = expansion.gpios("reset").unwrap().iter().next().unwrap();
+        assert_eq!(reset.controller().phandle(), Some(2));
+        assert_eq!(reset.cells(), [3, 1]);
+        // 两级 nexus
+        let enable = expansion.gpios("enable").unwrap().iter().next().unwrap();
+        assert_eq!(enable.controller().phandle(), Some(1));
+        assert_eq!((enable.line(), enable.flags()), (13, 1));
+        // 映射表中没有匹配项
+        assert!(expansion.gpios("wake").unwrap().iter().next().is_none());
+        assert!(expansion.gpios("").is_none());
+    }
+    #[test]
+    fn test_gpios() {
+        #[repr(align(8))]
+        struct AlignedBuffer {
+            pub data: [u8; RAW_DEVICE_TREE_HIFIVE.len()],
+        }
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE_HIFIVE],
+        });
+        aligned_data.data[..BUFFER_SIZE_HIFIVE].clone_from_slice(RAW_DEVICE_TREE_HIFIVE);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        let pcie = node.find("/soc/pcie@e00000000").unwrap();
+        let reset = pcie.gpios("reset").unwrap().iter().next().unwrap();
+        assert_eq!(reset.controller().phandle(), Some(0xb));
+        assert_eq!((reset.line(), reset.flags()), (8, 0));
+        let poweroff = node.find("/gpio-poweroff").unwrap();
+        let gpio = poweroff.gpios("").unwrap().iter().next().unwrap();
+        assert_eq!((gpio.line(), gpio.flags()), (2, 1));
+    }
+}
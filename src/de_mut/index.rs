@@ -0,0 +1,410 @@
+use super::node::Node;
+use super::{BodyCursor, Cursor, DtError, Dtb, RefDtb, RegConfig};
+
+/// 表示“没有”的序号。
+const NONE: u32 = u32::MAX;
+
+/// 结构索引中的一项，对应一个节点。
+///
+/// 所有位置都是结构块序号，节点之间的关系都是索引项序号。
+#[derive(Clone, Copy, Debug)]
+pub struct IndexEntry {
+    /// 节点名之后的位置。
+    body: u32,
+    /// 第一个属性的位置。
+    props_start: u32,
+    /// 第一个子节点的位置。
+    nodes_start: u32,
+    /// 节点结束符之后的位置。
+    end: u32,
+    parent: u32,
+    first_child: u32,
+    next_sibling: u32,
+    phandle: u32,
+    /// phandle 为 `序号 + 1` 的节点。
+    phandle_slot: u32,
+    address_cells: u32,
+    size_cells: u32,
+}
+
+impl IndexEntry {
+    /// 未使用的索引项，用于初始化缓冲区。
+    pub const EMPTY: Self = Self {
+        body: 0,
+        props_start: NONE,
+        nodes_start: NONE,
+        end: 0,
+        parent: NONE,
+        first_child: NONE,
+        next_sibling: NONE,
+        phandle: 0,
+        phandle_slot: NONE,
+        address_cells: 0,
+        size_cells: 0,
+    };
+}
+
+impl Default for IndexEntry {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+enum Storage<'a> {
+    Borrowed(&'a mut [IndexEntry]),
+    #[cfg(feature = "alloc")]
+    Owned(alloc::vec::Vec<IndexEntry>),
+}
+
+/// 设备树的结构索引。
+///
+/// 一次遍历结构块，记录每个节点的位置、父节点、第一个子节点、下一个兄弟节点、属性范围和 phandle。
+/// 通过 [`Dtb::attach_index`] 附加到设备树上之后，
+/// 查找子节点、父节点和 phandle 都不必再重新扫描结构块。
+/// 属性不在索引中，[`Node::get_prop`] 和 [`Node::props`] 仍逐个访问本节点的属性。
+///
+/// 设备树与它的结构块一样不带生命周期，因此只能附加 `'static` 的索引，
+/// 例如建立在静态缓冲区或泄漏的堆内存中的索引：
+///
+/// ```compile_fail
+/// # use serde_device_tree::{Dtb, DtbIndex, DtbPtr, IndexEntry};
+/// # fn f(ptr: DtbPtr) {
+/// let dtb = Dtb::from(ptr).share();
+/// let mut entries = [IndexEntry::EMPTY; 80];
+/// let index = DtbIndex::build(&dtb, &mut entries).unwrap();
+/// dtb.borrow_mut().attach_index(index);
+/// # }
+/// ```
+pub struct DtbIndex<'a> {
+    entries: Storage<'a>,
+    len: usize,
+}
+
+impl<'a> DtbIndex<'a> {
+    /// 建立索引需要的索引项数量，即节点数量。
+    pub fn required_len(dtb: RefDtb) -> usize {
+        Node::from_cursor(dtb, RegConfig::DEFAULT, BodyCursor::ROOT)
+            .descendants()
+            .count()
+    }
+
+    /// 在调用者提供的缓冲区中建立索引。
+    ///
+    /// 缓冲区不够大时返回 [`ErrorType::IndexBufferTooSmall`]；
+    /// `#address-cells`、`#size-cells` 或 `phandle` 不是 4 字节时返回解析错误。
+    ///
+    /// [`ErrorType::IndexBufferTooSmall`]: crate::error::ErrorType::IndexBufferTooSmall
+    pub fn build(dtb: RefDtb, entries: &'a mut [IndexEntry]) -> Result<Self, DtError> {
+        let len = build(dtb, entries)?;
+        Ok(Self {
+            entries: Storage::Borrowed(entries),
+            len,
+        })
+    }
+
+    /// 节点数量。
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// 如果索引中没有节点，返回 `true`。
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn entries(&self) -> &[IndexEntry] {
+        match &self.entries {
+            Storage::Borrowed(entries) => &entries[..self.len],
+            #[cfg(feature = "alloc")]
+            Storage::Owned(entries) => &entries[..self.len],
+        }
+    }
+
+    /// 找到节点体位于 `body` 的索引项。
+    pub(super) fn find(&self, body: BodyCursor) -> Option<usize> {
+        let body = body.block() as u32;
+        let entries = self.entries();
+        // 索引项按节点在结构块中的顺序排列
+        let i = entries.partition_point(|entry| entry.body < body);
+        (entries.get(i)?.body == body).then_some(i)
+    }
+
+    /// 用索引项构造节点。
+    pub(super) fn node<'de>(&self, dtb: RefDtb<'de>, i: usize) -> Node<'de> {
+        let entry = &self.entries()[i];
+        let cursor = |block: u32| (block != NONE).then(|| BodyCursor::at(block as _));
+        Node::from_parts(
+            dtb,
+            RegConfig {
+                address_cells: entry.address_cells as _,
+                size_cells: entry.size_cells as _,
+            },
+            BodyCursor::at(entry.body as _),
+            cursor(entry.props_start),
+            cursor(entry.nodes_start),
+        )
+    }
+
+    /// 节点结束符之后的位置。
+    pub(super) fn end(&self, i: usize) -> BodyCursor {
+        BodyCursor::at(self.entries()[i].end as _)
+    }
+
+    /// 父节点的索引项。
+    pub(super) fn parent(&self, i: usize) -> Option<usize> {
+        link(self.entries()[i].parent)
+    }
+
+    /// 子节点的索引项。
+    pub(super) fn children(&self, i: usize) -> impl Iterator<Item = usize> + '_ {
+        let entries = self.entries();
+        core::iter::successors(link(entries[i].first_child), |j| {
+            link(entries[*j].next_sibling)
+        })
+    }
+
+    /// 找到 phandle 对应的索引项。
+    pub(super) fn by_phandle(&self, phandle: u32) -> Option<usize> {
+        // 0 不是合法的 phandle，同时也是“没有 phandle”的标记
+        if phandle == 0 {
+            return None;
+        }
+        let entries = self.entries();
+        if let Some(slot) = (phandle as usize).checked_sub(1) {
+            if let Some(i) = entries.get(slot).and_then(|entry| link(entry.phandle_slot)) {
+                return Some(i);
+            }
+        }
+        // phandle 不连续时退回到逐项比较
+        entries.iter().position(|entry| entry.phandle == phandle)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl DtbIndex<'static> {
+    /// 在新分配的内存中建立索引。
+    ///
+    /// `#address-cells`、`#size-cells` 或 `phandle` 不是 4 字节时返回解析错误。
+    pub fn build_vec(dtb: RefDtb) -> Result<Self, DtError> {
+        let mut entries = alloc::vec![IndexEntry::EMPTY; Self::required_len(dtb)];
+        let len = build(dtb, &mut entries)?;
+        Ok(Self {
+            entries: Storage::Owned(entries),
+            len,
+        })
+    }
+}
+
+impl Dtb {
+    /// 附加结构索引，此后节点操作将使用索引。
+    ///
+    /// 索引必须是由同一个设备树建立的。
+    pub fn attach_index(&mut self, index: DtbIndex<'static>) {
+        self.index = Some(index);
+    }
+
+    /// 取下附加的结构索引。
+    pub fn detach_index(&mut self) -> Option<DtbIndex<'static>> {
+        self.index.take()
+    }
+}
+
+impl<'de> Node<'de> {
+    /// 如果附加了结构索引，用索引查找 phandle 对应的节点。
+    pub(crate) fn find_by_phandle_indexed(&self, phandle: u32) -> Option<Option<Node<'de>>> {
+        let dtb = self.dtb.borrow();
+        let index = dtb.index.as_ref()?;
+        Some(index.by_phandle(phandle).map(|i| index.node(self.dtb, i)))
+    }
+}
+
+#[inline]
+fn link(i: u32) -> Option<usize> {
+    (i != NONE).then_some(i as _)
+}
+
+/// 遍历结构块，填写索引项，返回节点数量。
+fn build(dtb: RefDtb, entries: &mut [IndexEntry]) -> Result<usize, DtError> {
+    let mut len = 0;
+    // 当前所在的节点；节点未结束时，`end` 暂存它的最后一个子节点
+    let mut current = NONE;
+    let mut cursor = BodyCursor::ROOT;
+    let mut reg = RegConfig::DEFAULT;
+    let mut body = Some(cursor);
+    loop {
+        // 进入一个节点
+        if let Some(node) = body.take() {
+            let Some(entry) = entries.get_mut(len) else {
+                return Err(DtError::index_buffer_too_small(
+                    len as _,
+                    node.file_index_on(dtb),
+                ));
+            };
+            *entry = IndexEntry {
+                body: node.block() as _,
+                parent: current,
+                end: NONE,
+                address_cells: reg.address_cells as _,
+                size_cells: reg.size_cells as _,
+                ..IndexEntry::EMPTY
+            };
+            if current != NONE {
+                let parent = &mut entries[current as usize];
+                let last = core::mem::replace(&mut parent.end, len as _);
+                if last == NONE {
+                    parent.first_child = len as _;
+                } else {
+                    entries[last as usize].next_sibling = len as _;
+                }
+            }
+            current = len as _;
+            len += 1;
+        }
+        let origin = cursor;
+        let entry = &mut entries[current as usize];
+        match cursor.move_on(dtb) {
+            Cursor::Prop(c) => {
+                if entry.props_start == NONE {
+                    entry.props_start = origin.block() as _;
+                }
+                let (name, next) = c.name_on(dtb);
+                match name {
+                    "#address-cells" => entry.address_cells = c.map_u32_on(dtb)?,
+                    "#size-cells" => entry.size_cells = c.map_u32_on(dtb)?,
+                    "phandle" | "linux,phandle" => entry.phandle = c.map_u32_on(dtb)?,
+                    _ => {}
+                }
+                cursor = next;
+            }
+            Cursor::Title(c) => {
+                if entry.nodes_start == NONE {
+                    entry.nodes_start = cursor.block() as _;
+                }
+                reg = RegConfig {
+                    address_cells: entry.address_cells as _,
+                    size_cells: entry.size_cells as _,
+                };
+                let (_, next) = c.split_on(dtb);
+                cursor = next;
+                body = Some(next);
+            }
+            Cursor::End => {
+                cursor.step_n(1);
+                entry.end = cursor.block() as _;
+                current = entry.parent;
+                if current == NONE {
+                    break;
+                }
+            }
+        }
+    }
+    // phandle 表
+    for i in 0..len {
+        let phandle = entries[i].phandle as usize;
+        if (1..=len).contains(&phandle) {
+            entries[phandle - 1].phandle_slot = i as _;
+        }
+    }
+    Ok(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::{Error, ErrorType};
+    use crate::{buildin::Node, from_raw_mut, Dtb, DtbIndex, DtbPtr, IndexEntry};
+
+    const RAW_DEVICE_TREE: &[u8] = include_bytes!("../../examples/hifive-unmatched-a00.dtb");
+    const BUFFER_SIZE: usize = RAW_DEVICE_TREE.len();
+    const RAW_DEVICE_TREE_MALFORMED: &[u8] = include_bytes!("../../examples/malformed-cells.dtb");
+    const BUFFER_SIZE_MALFORMED: usize = RAW_DEVICE_TREE_MALFORMED.len();
+    /// 遍历整棵树，记录每个节点的路径、父节点、子节点和属性数量。
+    fn summary(node: &Node) -> Vec<String> {
+        node.descendants()
+            .map(|(depth, node)| {
+                format!(
+                    "{depth} {} {:?} {} {} {:?}",
+                    node.path(),
+                    node.parent().map(|parent| parent.path().to_string()),
+                    node.nodes().count(),
+                    node.props().count(),
+                    node.reg
+                )
+            })
+            .collect()
+    }
+    #[test]
+    fn test_index() {
+        #[repr(align(8))]
+        struct AlignedBuffer {
+            pub data: [u8; RAW_DEVICE_TREE.len()],
+        }
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE],
+        });
+        aligned_data.data[..BUFFER_SIZE].clone_from_slice(RAW_DEVICE_TREE);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        let expected = summary(&node);
+        let phandles = (0..16)
+            .map(|phandle| {
+                node.find_by_phandle(phandle)
+                    .map(|node| node.path().to_string())
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(DtbIndex::required_len(&dtb), 70);
+        let mut small = [IndexEntry::EMPTY; 69];
+        assert!(matches!(
+            DtbIndex::build(&dtb, &mut small),
+            Err(Error::Typed {
+                error_type: ErrorType::IndexBufferTooSmall { given_length: 69 },
+                ..
+            })
+        ));
+        let entries = Box::leak(Box::new([IndexEntry::EMPTY; 80]));
+        let index = DtbIndex::build(&dtb, entries).unwrap();
+        assert_eq!(index.len(), 70);
+        dtb.borrow_mut().attach_index(index);
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        assert_eq!(summary(&node), expected);
+        assert_eq!(
+            (0..16)
+                .map(|phandle| node
+                    .find_by_phandle(phandle)
+                    .map(|node| node.path().to_string()))
+                .collect::<Vec<_>>(),
+            phandles
+        );
+        assert!(node.find("/soc/ethernet/ethernet-phy").is_some());
+        assert!(dtb.borrow_mut().detach_index().is_some());
+    }
+
+    #[test]
+    fn test_index_malformed() {
+        #[repr(align(8))]
+        struct AlignedBuffer {
+            pub data: [u8; RAW_DEVICE_TREE_MALFORMED.len()],
+        }
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE_MALFORMED],
+        });
+        aligned_data.data[..BUFFER_SIZE_MALFORMED].clone_from_slice(RAW_DEVICE_TREE_MALFORMED);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        // 根节点的 `#size-cells` 不是 4 字节
+        let mut entries = [IndexEntry::EMPTY; 4];
+        assert!(matches!(
+            DtbIndex::build(&dtb, &mut entries),
+            Err(Error::Typed {
+                error_type: ErrorType::BuildInTypeParseFailed { expected: "u32" },
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,257 @@
+use super::cell_array::CellArray;
+use super::node::Node;
+
+/// 物理内存区域的类型。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MemoryKind {
+    /// 可以交给页帧分配器使用。
+    Usable,
+    /// 被 `no-map` 的保留内存或内存保留块占用，不能使用。
+    Reserved,
+}
+
+/// 一段物理内存区域 `[start, end)`。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MemoryRegion {
+    pub start: u64,
+    pub end: u64,
+    pub kind: MemoryKind,
+}
+
+/// 写入调用者提供的切片的有序区域列表。
+struct RegionList<'a> {
+    regions: &'a mut [MemoryRegion],
+    len: usize,
+}
+
+impl<'de> Node<'de> {
+    /// 计算物理内存布局，写入 `out`，返回区域数量。
+    ///
+    /// 内存来自所有可用的 `device_type = "memory"` 节点，
+    /// 有 `linux,usable-memory` 属性时以其代替 `reg`。
+    /// 其中被 `/reserved-memory` 下带 `no-map` 的可用子节点或内存保留块覆盖的部分标记为保留，
+    /// 其余部分可用；内存以外的保留区域被忽略。
+    /// 结果按地址排序，相邻的同类区域被合并。
+    ///
+    /// `out` 放不下所有区域时返回 `None`。
+    pub fn memory_map(&self, out: &mut [MemoryRegion]) -> Option<usize> {
+        let root = self.root();
+        let mut list = RegionList {
+            regions: out,
+            len: 0,
+        };
+        let widths = [root.reg.address_cells, root.reg.size_cells];
+        for memory in root.select("/*[device_type=\"memory\"]") {
+            if !memory.is_enabled() {
+                continue;
+            }
+            let Some(prop) = memory
+                .get_prop("linux,usable-memory")
+                .or_else(|| memory.get_prop("reg"))
+            else {
+                continue;
+            };
+            let cells = CellArray::new(prop.deserialize::<&[u8]>());
+            for [base, size] in cells.entries(widths).into_iter().flatten() {
+                list.add_usable(base, base.saturating_add(size))?;
+            }
+        }
+        if let Some(reserved) = root.find("/reserved-memory") {
+            let widths = [reserved.reg.address_cells, reserved.reg.size_cells];
+            for child in reserved.select("*[no-map][reg]") {
+                if !child.is_enabled() {
+                    continue;
+                }
+                let cells = CellArray::new(child.get_prop("reg")?.deserialize::<&[u8]>());
+                for [base, size] in cells.entries(widths).into_iter().flatten() {
+                    list.reserve(base, base.saturating_add(size))?;
+                }
+            }
+        }
+        for (base, size) in self.dtb.borrow().memreserve() {
+            list.reserve(base, base.saturating_add(size))?;
+        }
+        list.coalesce();
+        Some(list.len)
+    }
+}
+
+impl RegionList<'_> {
+    /// 在 `index` 处插入一个区域。
+    fn insert(&mut self, index: usize, region: MemoryRegion) -> Option<()> {
+        if self.len == self.regions.len() {
+            return None;
+        }
+        self.regions[index..=self.len].rotate_right(1);
+        self.regions[index] = region;
+        self.len += 1;
+        Some(())
+    }
+
+    /// 加入一段可用内存，与重叠或相邻的区域合并。
+    fn add_usable(&mut self, start: u64, end: u64) -> Option<()> {
+        if start >= end {
+            return Some(());
+        }
+        let index = self.regions[..self.len].partition_point(|r| r.start < start);
+        self.insert(
+            index,
+            MemoryRegion {
+                start,
+                end,
+                kind: MemoryKind::Usable,
+            },
+        )?;
+        self.coalesce();
+        Some(())
+    }
+
+    /// 将 `[start, end)` 与可用内存重叠的部分标记为保留。
+    fn reserve(&mut self, start: u64, end: u64) -> Option<()> {
+        let mut i = 0;
+        while i < self.len {
+            let region = self.regions[i];
+            if region.kind == MemoryKind::Reserved || region.end <= start || end <= region.start {
+                i += 1;
+                continue;
+            }
+            // 切分为左侧可用、中间保留、右侧可用三段
+            let middle_start = region.start.max(start);
+            let middle_end = region.end.min(end);
+            self.regions[i] = MemoryRegion {
+                start: middle_start,
+                end: middle_end,
+                kind: MemoryKind::Reserved,
+            };
+            if region.start < middle_start {
+                self.insert(
+                    i,
+                    MemoryRegion {
+                        end: middle_start,
+                        ..region
+                    },
+                )?;
+                i += 1;
+            }
+            if middle_end < region.end {
+                self.insert(
+                    i + 1,
+                    MemoryRegion {
+                        start: middle_end,
+                        ..region
+                    },
+                )?;
+                i += 1;
+            }
+            i += 1;
+        }
+        Some(())
+    }
+
+    /// 合并重叠或相邻的同类区域。
+    fn coalesce(&mut self) {
+        let mut i = 0;
+        while i + 1 < self.len {
+            let (current, next) = (self.regions[i], self.regions[i + 1]);
+            if current.kind == next.kind && next.start <= current.end {
+                self.regions[i].end = current.end.max(next.end);
+                self.regions[i + 1..self.len].rotate_left(1);
+                self.len -= 1;
+            } else {
+                i += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buildin::{MemoryKind, MemoryRegion, Node};
+    use crate::{from_raw_mut, Dtb, DtbPtr};
+
+    const RAW_DEVICE_TREE: &[u8] = include_bytes!("../../examples/memory-map.dtb");
+    const BUFFER_SIZE: usize = RAW_DEVICE_TREE.len();
+    const RAW_DEVICE_TREE_QEMU: &[u8] = include_bytes!("../../examples/qemu-virt.dtb");
+    const BUFFER_SIZE_QEMU: usize = RAW_DEVICE_TREE_QEMU.len();
+    const fn region(start: u64, end: u64, kind: MemoryKind) -> MemoryRegion {
+        MemoryRegion { start, end, kind }
+    }
+    #[test]
+    fn test_memory_map() {
+        #[repr(align(8))]
+        struct AlignedBuffer {
+            pub data: [u8; RAW_DEVICE_TREE.len()],
+        }
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE],
+        });
+        aligned_data.data[..BUFFER_SIZE].clone_from_slice(RAW_DEVICE_TREE);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        assert_eq!(
+            dtb.borrow().memreserve().collect::<Vec<_>>(),
+            [(0x8000_0000, 0x1_0000), (0xd800_0000, 0x1000)]
+        );
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        let mut out = [region(0, 0, MemoryKind::Usable); 8];
+        let len = node.memory_map(&mut out).unwrap();
+        use MemoryKind::*;
+        assert_eq!(
+            out[..len],
+            [
+                region(0x8000_0000, 0x8020_0000, Reserved),
+                region(0x8020_0000, 0x9ff0_0000, Usable),
+                region(0x9ff0_0000, 0xa000_0000, Reserved),
+                region(0xc000_0000, 0xd800_0000, Usable),
+                region(0xd800_0000, 0xd800_1000, Reserved),
+                region(0xd800_1000, 0xe000_0000, Usable),
+            ]
+        );
+        // 空间不足
+        assert_eq!(node.memory_map(&mut out[..5]), None);
+    }
+
+    #[test]
+    fn test_memory_map_qemu() {
+        #[repr(align(8))]
+        struct AlignedBuffer {
+            pub data: [u8; RAW_DEVICE_TREE_QEMU.len()],
+        }
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE_QEMU],
+        });
+        aligned_data.data[..BUFFER_SIZE_QEMU].clone_from_slice(RAW_DEVICE_TREE_QEMU);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        let mut out = [region(0, 0, MemoryKind::Usable); 1];
+        assert_eq!(node.memory_map(&mut out), Some(1));
+        assert_eq!(
+            out[0],
+            region(0x8000_0000, 0x1_8000_0000, MemoryKind::Usable)
+        );
+    }
+    #[test]
+    fn test_memreserve_out_of_bounds() {
+        #[repr(align(8))]
+        struct AlignedBuffer {
+            pub data: [u8; RAW_DEVICE_TREE.len()],
+        }
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE],
+        });
+        aligned_data.data[..BUFFER_SIZE].clone_from_slice(RAW_DEVICE_TREE);
+        let mut slice = aligned_data.data.to_vec();
+        // 把首部的 off_mem_rsvmap 改到文件之外
+        let off = (BUFFER_SIZE as u32 + 0x100).to_be_bytes();
+        slice[16..20].copy_from_slice(&off);
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        assert_eq!(dtb.borrow().memreserve().count(), 0);
+    }
+}
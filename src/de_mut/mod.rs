@@ -4,27 +4,63 @@
 use crate::error::Error as DtError;
 use serde::de;
 
+mod ancestors;
+mod bytes;
+mod cell_array;
 mod cursor;
 mod data;
+mod descendants;
+mod dma;
 // mod group;
+mod gpio;
+mod index;
+mod memory;
 mod node;
 mod node_seq;
+mod number;
+mod offset;
+mod phandle;
 mod reg;
+mod select;
+mod status;
 mod str_seq;
+mod unit_address;
+mod walk;
 // mod r#struct;
 mod struct_access;
 mod structs;
+mod translate;
 
 const VALUE_DESERIALIZER_NAME: &str = "$serde_device_tree$de_mut$ValueDeserializer";
 
-pub use structs::{Dtb, DtbPtr};
+pub use index::{DtbIndex, IndexEntry};
+pub use structs::{Dtb, DtbPtr, SharedDtb};
+pub use walk::{DtVisitor, WalkControl};
 pub mod buildin {
-    pub use super::{node::Node, node_seq::NodeSeq, reg::Reg, str_seq::StrSeq};
+    pub use super::{
+        ancestors::NodePath,
+        bytes::{Bytes, FixedBytes, MacAddress},
+        cell_array::{CellArray, CellArrayIter},
+        descendants::Descendants,
+        gpio::{Gpio, Gpios},
+        memory::{MemoryKind, MemoryRegion},
+        node::{Node, NodeIter},
+        node_seq::NodeSeq,
+        number::Number,
+        offset::NodeOffset,
+        phandle::{PhandleArg, PhandleArgs, PhandleArgsIter},
+        reg::Reg,
+        select::Select,
+        status::Status,
+        str_seq::{StrSeq, StrSeqIter, StrSeqTryIter},
+        unit_address::UnitAddress,
+    };
 }
 
 use cursor::{BodyCursor, Cursor, PropCursor};
 use data::{ValueCursor, ValueDeserializer};
 use reg::RegConfig;
+use str_seq::StrSeq;
 use struct_access::{StructAccess, StructAccessType, Temp};
 use structs::{RefDtb, StructureBlock, BLOCK_LEN};
 
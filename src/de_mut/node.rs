@@ -9,11 +9,11 @@ use serde::{de, Deserialize};
 #[allow(unused)]
 #[derive(Clone)]
 pub struct Node<'de> {
-    dtb: RefDtb<'de>,
-    reg: RegConfig,
-    cursor: BodyCursor,
+    pub(super) dtb: RefDtb<'de>,
+    pub(super) reg: RegConfig,
+    pub(super) cursor: BodyCursor,
     props_start: Option<BodyCursor>,
-    nodes_start: Option<BodyCursor>,
+    pub(super) nodes_start: Option<BodyCursor>,
 }
 
 /// 节点迭代器。
@@ -26,7 +26,7 @@ pub struct NodeIter<'de, 'b> {
 /// 节点对象。
 pub struct NodeItem<'de> {
     dtb: RefDtb<'de>,
-    reg: RegConfig,
+    pub(super) reg: RegConfig,
     node: BodyCursor,
     name: &'de str,
 }
@@ -49,6 +49,71 @@ pub struct PropItem<'de> {
 }
 
 impl<'de> Node<'de> {
+    /// 从节点名之后的光标直接构造节点，不必遍历整个子树。
+    ///
+    /// `reg` 是从父节点继承的地址空间格式，节点自己的 `#address-cells` 和 `#size-cells` 优先；
+    /// 这两个属性不是 4 字节时忽略它们，仍使用继承的格式。
+    pub(super) fn from_cursor(dtb: RefDtb<'de>, mut reg: RegConfig, cursor: BodyCursor) -> Self {
+        if let Some(index) = dtb.borrow().index.as_ref() {
+            if let Some(i) = index.find(cursor) {
+                return index.node(dtb, i);
+            }
+        }
+        let mut body = cursor;
+        let mut props_start = None;
+        let nodes_start = loop {
+            let origin_cursor = body;
+            match body.move_on(dtb) {
+                Cursor::Prop(c) => {
+                    if props_start.is_none() {
+                        props_start = Some(origin_cursor);
+                    }
+                    let (name, next) = c.name_on(dtb);
+                    match name {
+                        "#address-cells" => {
+                            if let Ok(cells) = c.map_u32_on(dtb) {
+                                reg.address_cells = cells as _;
+                            }
+                        }
+                        "#size-cells" => {
+                            if let Ok(cells) = c.map_u32_on(dtb) {
+                                reg.size_cells = cells as _;
+                            }
+                        }
+                        _ => {}
+                    }
+                    body = next;
+                }
+                Cursor::Title(_) => break Some(body),
+                Cursor::End => break None,
+            }
+        };
+        Node {
+            dtb,
+            reg,
+            cursor,
+            props_start,
+            nodes_start,
+        }
+    }
+
+    /// 用已知的属性和子节点位置构造节点。
+    pub(super) fn from_parts(
+        dtb: RefDtb<'de>,
+        reg: RegConfig,
+        cursor: BodyCursor,
+        props_start: Option<BodyCursor>,
+        nodes_start: Option<BodyCursor>,
+    ) -> Self {
+        Node {
+            dtb,
+            reg,
+            cursor,
+            props_start,
+            nodes_start,
+        }
+    }
+
     pub fn deserialize<T: Deserialize<'de>>(&self) -> T {
         use super::ValueCursor;
         T::deserialize(&mut ValueDeserializer {
@@ -78,9 +143,17 @@ impl<'de> Node<'de> {
     }
 
     /// 尝试获得指定属性
-    pub fn get_prop<'b>(&'b self, name: &str) -> Option<PropItem<'b>> {
+    ///
+    /// 结构索引只记录属性的起始位置，不记录属性名，
+    /// 因此附加索引后仍要在本节点的属性中逐个比较名字。
+    pub fn get_prop(&self, name: &str) -> Option<PropItem<'de>> {
         self.props().find(|prop| prop.get_name() == name)
     }
+
+    /// 获得设备树的根节点。
+    pub(crate) fn root(&self) -> Node<'de> {
+        Self::from_cursor(self.dtb, RegConfig::DEFAULT, BodyCursor::ROOT)
+    }
 }
 
 impl Debug for Node<'_> {
@@ -123,15 +196,20 @@ impl<'de> Iterator for NodeIter<'de, '_> {
             self.i += 1;
             let dtb = self.node.dtb;
             if let Cursor::Title(c) = cursor.move_on(dtb) {
-                let (name, _) = c.split_on(dtb);
-                let node_cursor = c.take_node_on(dtb, name);
+                let (name, body) = c.split_on(dtb);
+                // 有结构索引时直接跳到子树之后
+                let end = dtb
+                    .borrow()
+                    .index
+                    .as_ref()
+                    .and_then(|index| index.find(body).map(|i| index.end(i)));
                 let res = Some(Self::Item {
                     dtb,
                     reg: self.node.reg,
-                    node: node_cursor.skip_cursor,
+                    node: body,
                     name,
                 });
-                *cursor = node_cursor.next_cursor;
+                *cursor = end.unwrap_or_else(|| c.take_node_on(dtb, name).next_cursor);
                 res
             } else {
                 None
@@ -201,7 +279,8 @@ impl<'de> Deserialize<'de> for Node<'_> {
                     if key == "/" {
                         self_cursor = match value.cursor {
                             ValueCursor::Body(cursor) => Some(cursor),
-                            ValueCursor::Node(result) => Some(result.next_cursor),
+                            // 节点体在节点名之后，`next_cursor` 已经是下一个兄弟节点
+                            ValueCursor::Node(result) => Some(result.skip_cursor),
                             _ => {
                                 unreachable!("root of NodeSeq shouble be body cursor")
                             }
@@ -244,6 +323,11 @@ impl<'de> Deserialize<'de> for Node<'_> {
 }
 
 impl<'de> NodeItem<'de> {
+    /// 获得对应的节点，不必遍历整个子树。
+    pub(crate) fn to_node(&self) -> Node<'de> {
+        Node::from_cursor(self.dtb, self.reg, self.node)
+    }
+
     /// 反序列化一个节点的内容。
     pub fn deserialize<T: Deserialize<'de>>(&self) -> T {
         T::deserialize(&mut ValueDeserializer {
@@ -277,7 +361,7 @@ impl<'de> NodeItem<'de> {
 }
 
 impl<'de> PropItem<'de> {
-    pub fn get_name(&self) -> &str {
+    pub fn get_name(&self) -> &'de str {
         self.name
     }
     pub fn deserialize<T: Deserialize<'de>>(&self) -> T {
@@ -293,7 +377,11 @@ impl<'de> PropItem<'de> {
 
 #[cfg(test)]
 mod tests {
-    use crate::{buildin::Node, from_raw_mut, Dtb, DtbPtr};
+    use crate::{
+        buildin::{Node, StrSeq},
+        from_raw_mut, Dtb, DtbPtr,
+    };
+    use serde_derive::Deserialize;
     const RAW_DEVICE_TREE: &[u8] = include_bytes!("../../examples/hifive-unmatched-a00.dtb");
     const BUFFER_SIZE: usize = RAW_DEVICE_TREE.len();
     #[repr(align(8))]
@@ -314,4 +402,27 @@ mod tests {
         let prop = node.get_prop("compatible");
         assert!(prop.is_some());
     }
+    #[derive(Deserialize)]
+    struct Tree<'a> {
+        soc: Node<'a>,
+    }
+    #[derive(Deserialize)]
+    struct Soc<'a> {
+        compatible: StrSeq<'a>,
+    }
+    #[test]
+    fn test_field_node_cursor() {
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE],
+        });
+        aligned_data.data[..BUFFER_SIZE].clone_from_slice(RAW_DEVICE_TREE);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        // 作为结构体字段的节点指向自己的节点体，而不是下一个兄弟节点
+        let t: Tree = from_raw_mut(&dtb).unwrap();
+        let soc: Soc = t.soc.deserialize();
+        assert_eq!(soc.compatible.get(2), Some("simple-bus"));
+    }
 }
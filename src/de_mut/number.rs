@@ -0,0 +1,112 @@
+use super::node::Node;
+use super::{ValueCursor, ValueDeserializer};
+use core::fmt::{Debug, Display};
+use serde::{de, Deserialize};
+
+/// 占用一个或两个单元的数值属性。
+///
+/// `timebase-frequency`、`clock-frequency`、`linux,initrd-start` 等属性
+/// 在不同的板子上可能编码为 32 位或 64 位，统一解析为 `u64`。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Number(pub u64);
+
+impl Number {
+    /// 从属性值解析，长度不是 4 或 8 字节时返回 `None`。
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        match data.len() {
+            4 => Some(Self(u32::from_be_bytes(data.try_into().unwrap()) as u64)),
+            8 => Some(Self(u64::from_be_bytes(data.try_into().unwrap()))),
+            _ => None,
+        }
+    }
+
+    /// 数值。
+    pub const fn get(&self) -> u64 {
+        self.0
+    }
+}
+
+impl From<Number> for u64 {
+    fn from(value: Number) -> Self {
+        value.0
+    }
+}
+
+impl Display for Number {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl<'de> Deserialize<'de> for Number {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value_deserialzer = ValueDeserializer::deserialize(deserializer)?;
+        let data = match value_deserialzer.cursor {
+            ValueCursor::Prop(_, cursor) => cursor.data_on(value_deserialzer.dtb),
+            _ => {
+                unreachable!("Number Deserialize should only be called by prop cursor")
+            }
+        };
+        Self::from_bytes(data).ok_or_else(|| de::Error::invalid_length(data.len(), &"4 or 8 bytes"))
+    }
+}
+
+impl Node<'_> {
+    /// 获得名为 `name` 的一个或两个单元的数值属性。
+    ///
+    /// 属性不存在或长度不符时返回 `None`。
+    pub fn get_number(&self, name: &str) -> Option<u64> {
+        Number::from_bytes(self.get_prop(name)?.deserialize::<&[u8]>()).map(u64::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buildin::{Node, Number};
+    use crate::{from_raw_mut, Dtb, DtbPtr};
+    use serde_derive::Deserialize;
+
+    const RAW_DEVICE_TREE: &[u8] = include_bytes!("../../examples/qemu-virt.dtb");
+    const BUFFER_SIZE: usize = RAW_DEVICE_TREE.len();
+    #[repr(align(8))]
+    struct AlignedBuffer {
+        pub data: [u8; RAW_DEVICE_TREE.len()],
+    }
+    #[derive(Deserialize)]
+    struct Cpus {
+        #[serde(rename = "timebase-frequency")]
+        timebase_frequency: Number,
+    }
+    #[derive(Deserialize)]
+    struct WrongLength {
+        #[serde(rename = "compatible")]
+        _compatible: Number,
+    }
+    #[test]
+    fn test_number() {
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE],
+        });
+        aligned_data.data[..BUFFER_SIZE].clone_from_slice(RAW_DEVICE_TREE);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        let cpus: Cpus = node.find("/cpus").unwrap().deserialize();
+        assert_eq!(cpus.timebase_frequency, Number(10_000_000));
+        assert!(from_raw_mut::<WrongLength>(&dtb).is_err());
+        let serial = node.find("/soc/serial@10000000").unwrap();
+        assert_eq!(serial.get_number("clock-frequency"), Some(0x38_4000));
+        // 两个单元
+        let memory = node.find("/memory@80000000").unwrap();
+        let size = memory.get_prop("reg").unwrap().deserialize::<&[u8]>();
+        assert_eq!(Number::from_bytes(&size[8..]), Some(Number(0x1_0000_0000)));
+        // 四个单元的属性不是数值
+        assert_eq!(memory.get_number("reg"), None);
+        assert_eq!(Number::from_bytes(&[0; 2]), None);
+    }
+}
@@ -0,0 +1,134 @@
+use super::cursor::MoveResult;
+use super::node::Node;
+use super::{BodyCursor, DtError, RefDtb, RegConfig, BLOCK_LEN};
+
+/// 节点在结构块中的位置。
+///
+/// 节点位置不借用设备树，可以保存在驱动状态中，需要时再用 [`SharedDtb::node_at`] 取回节点。
+///
+/// 它是节点名之后的位置，而 libfdt 的偏移指向节点起始符，两者不能互换。
+///
+/// [`SharedDtb::node_at`]: crate::SharedDtb::node_at
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeOffset(usize);
+
+impl NodeOffset {
+    /// 从 [`NodeOffset::into_raw`] 得到的整数恢复节点位置。
+    ///
+    /// 这个位置是否真的是节点，由 [`SharedDtb::node_at`] 检查。
+    ///
+    /// [`SharedDtb::node_at`]: crate::SharedDtb::node_at
+    pub const fn from_raw(raw: usize) -> Self {
+        Self(raw)
+    }
+
+    /// 节点名之后的位置相对结构块的字节偏移。
+    pub const fn into_raw(self) -> usize {
+        self.0
+    }
+}
+
+impl Node<'_> {
+    /// 获得节点的位置。
+    pub fn offset(&self) -> NodeOffset {
+        NodeOffset(self.cursor.block() * BLOCK_LEN)
+    }
+}
+
+/// 取回位于 `offset` 的节点。
+pub(super) fn node_at(dtb: RefDtb, offset: NodeOffset) -> Result<Node, DtError> {
+    let file_index = offset.0.saturating_add(dtb.borrow().off_dt_struct());
+    let len = dtb.borrow().structure.len();
+    if !offset.0.is_multiple_of(BLOCK_LEN) {
+        return Err(DtError::expected_struct_begin());
+    }
+    let block = offset.0 / BLOCK_LEN;
+    if block >= len {
+        return Err(DtError::structure_index_overflow(
+            offset.0 as _,
+            (len * BLOCK_LEN) as _,
+            file_index,
+        ));
+    }
+    let cursor = BodyCursor::at(block);
+    if !is_node_body(dtb, cursor) {
+        return Err(DtError::expected_struct_begin());
+    }
+    let node = Node::from_cursor(dtb, RegConfig::DEFAULT, cursor);
+    // 地址空间格式从父节点继承
+    Ok(match node.parent() {
+        Some(parent) => Node::from_cursor(dtb, parent.reg, cursor),
+        None => node,
+    })
+}
+
+/// 检查 `target` 是否是某个节点名之后的位置。
+///
+/// 属性值中也可能出现与节点起始符相同的数据，因此必须从头按结构遍历。
+fn is_node_body(dtb: RefDtb, target: BodyCursor) -> bool {
+    if let Some(index) = dtb.borrow().index.as_ref() {
+        return index.find(target).is_some();
+    }
+    let mut cursor = BodyCursor::at(0);
+    let mut last = MoveResult::Others;
+    while cursor < target && !cursor.is_complete_on(dtb) {
+        last = cursor.move_next(dtb);
+    }
+    cursor == target && matches!(last, MoveResult::In)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buildin::{Node, NodeOffset, Reg};
+    use crate::{from_raw_mut, Dtb, DtbIndex, DtbPtr, IndexEntry, SharedDtb};
+
+    const RAW_DEVICE_TREE: &[u8] = include_bytes!("../../examples/hifive-unmatched-a00.dtb");
+    const BUFFER_SIZE: usize = RAW_DEVICE_TREE.len();
+    #[repr(align(8))]
+    struct AlignedBuffer {
+        pub data: [u8; RAW_DEVICE_TREE.len()],
+    }
+    #[test]
+    fn test_node_at() {
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE],
+        });
+        aligned_data.data[..BUFFER_SIZE].clone_from_slice(RAW_DEVICE_TREE);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let offsets = {
+            let node: Node = from_raw_mut(&dtb).unwrap();
+            let root = node.offset();
+            let pmic = node.find("/soc/i2c@10030000/pmic@58").unwrap().offset();
+            let serial = node.find("/soc/serial@10010000").unwrap().offset();
+            [root, pmic, serial]
+        };
+        let check = |offsets: &[NodeOffset]| {
+            let root = dtb.node_at(offsets[0]).unwrap();
+            assert_eq!(root.path(), "/");
+            let pmic = dtb.node_at(offsets[1]).unwrap();
+            assert_eq!(pmic.path(), "/soc/i2c@10030000/pmic@58");
+            assert_eq!(pmic.offset(), offsets[1]);
+            // 地址空间格式从父节点继承
+            let serial = dtb.node_at(offsets[2]).unwrap();
+            let reg = serial.get_prop("reg").unwrap().deserialize::<Reg>();
+            assert_eq!(reg.iter().next().unwrap().0, 0x10010000..0x10011000);
+
+            let raw = NodeOffset::from_raw(offsets[1].into_raw());
+            assert_eq!(raw, offsets[1]);
+            // 属性中间、未对齐和越界的位置
+            for raw in [offsets[1].into_raw() + 4, 1, usize::MAX / 4 * 4] {
+                assert!(dtb.node_at(NodeOffset::from_raw(raw)).is_err());
+            }
+        };
+        check(&offsets);
+
+        let entries = Box::leak(Box::new([IndexEntry::EMPTY; 80]));
+        let index = DtbIndex::build(&dtb, entries).unwrap();
+        dtb.borrow_mut().attach_index(index);
+        check(&offsets);
+        dtb.borrow_mut().detach_index();
+    }
+}
@@ -0,0 +1,360 @@
+use super::{node::Node, str_seq::StrSeqTryIter, StrSeq, BLOCK_LEN};
+use core::fmt::Debug;
+
+/// 说明符的最大单元数。
+const SPECIFIER_MAX_CELLS: usize = 8;
+
+/// nexus 节点的最大映射层数，防止映射表构成环。
+const NEXUS_MAX_DEPTH: usize = 16;
+
+/// 带参数的 phandle 列表。
+///
+/// `clocks = <&prci 7>, <&hfclk>;` 这样的属性由若干项组成，
+/// 每项是提供者节点的 phandle，后接提供者 `#clock-cells` 指定数量的参数。
+/// 如果消费者节点上有对应的 `clock-names` 属性，每一项会按顺序获得一个名字。
+///
+/// `resets`、`dmas`、`power-domains` 等属性都遵循这种格式，只是参数数量属性的名字不同。
+pub struct PhandleArgs<'de> {
+    node: Node<'de>,
+    data: &'de [u8],
+    cells: &'static str,
+    names: Option<StrSeq<'de>>,
+}
+
+/// phandle 列表迭代器。
+pub struct PhandleArgsIter<'de, 'b> {
+    args: &'b PhandleArgs<'de>,
+    data: &'de [u8],
+    /// 与各项同步前进的名字，不是合法 UTF-8 的名字不影响后续名字的对应关系。
+    names: Option<StrSeqTryIter<'de>>,
+}
+
+/// phandle 列表中的一项。
+pub struct PhandleArg<'de> {
+    phandle: u32,
+    provider: Node<'de>,
+    args: &'de [u8],
+    name: Option<&'de str>,
+}
+
+impl<'de> Node<'de> {
+    /// 解析名为 `name` 的 phandle 列表，每项的参数数量由提供者节点的 `cells` 属性决定。
+    ///
+    /// 名字属性由 `name` 去掉末尾的 `s` 再加上 `-names` 得到，例如 `clocks` 对应 `clock-names`。
+    pub fn phandle_args(&self, name: &str, cells: &'static str) -> Option<PhandleArgs<'de>> {
+        let data = self.get_prop(name)?.deserialize::<&[u8]>();
+        let stem = name.strip_suffix('s').unwrap_or(name);
+        let names = self
+            .props()
+            .find(|prop| prop.get_name().strip_suffix("-names") == Some(stem))
+            .map(|prop| prop.deserialize::<StrSeq>());
+        Some(PhandleArgs {
+            node: self.clone(),
+            data,
+            cells,
+            names,
+        })
+    }
+
+    /// 解析 `clocks` 属性。
+    #[inline]
+    pub fn clocks(&self) -> Option<PhandleArgs<'de>> {
+        self.phandle_args("clocks", "#clock-cells")
+    }
+
+    /// 解析 `resets` 属性。
+    #[inline]
+    pub fn resets(&self) -> Option<PhandleArgs<'de>> {
+        self.phandle_args("resets", "#reset-cells")
+    }
+
+    /// 解析 `dmas` 属性。
+    #[inline]
+    pub fn dmas(&self) -> Option<PhandleArgs<'de>> {
+        self.phandle_args("dmas", "#dma-cells")
+    }
+
+    /// 解析 `power-domains` 属性。
+    #[inline]
+    pub fn power_domains(&self) -> Option<PhandleArgs<'de>> {
+        self.phandle_args("power-domains", "#power-domain-cells")
+    }
+}
+
+impl<'de> PhandleArgs<'de> {
+    /// 构造一个依次访问每一项的迭代器。
+    pub fn iter<'b>(&'b self) -> PhandleArgsIter<'de, 'b> {
+        PhandleArgsIter {
+            args: self,
+            data: self.data,
+            names: self.names.as_ref().map(StrSeq::try_iter),
+        }
+    }
+
+    /// 按 `*-names` 属性中的名字查找一项。
+    pub fn by_name(&self, name: &str) -> Option<PhandleArg<'de>> {
+        self.iter().find(|arg| arg.name == Some(name))
+    }
+}
+
+impl Debug for PhandleArgs<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'de> Iterator for PhandleArgsIter<'de, '_> {
+    type Item = PhandleArg<'de>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.data.len() < BLOCK_LEN {
+                return None;
+            }
+            let (phandle, rest) = self.data.split_at(BLOCK_LEN);
+            let phandle = u32::from_be_bytes(phandle.try_into().unwrap());
+            let name = self
+                .names
+                .as_mut()
+                .and_then(|names| names.next())
+                .and_then(Result::ok);
+            // 值为 0 的 phandle 是占位用的空项，没有参数
+            if phandle == 0 {
+                self.data = rest;
+                continue;
+            }
+            // 找不到提供者就无法得知参数数量，后续的项都无法解析
+            let Some(provider) = self.args.node.find_by_phandle(phandle) else {
+                self.data = &[];
+                return None;
+            };
+            let count = provider
+                .get_prop(self.args.cells)
+                .map_or(0, |prop| prop.deserialize::<u32>()) as usize;
+            if rest.len() < count * BLOCK_LEN {
+                self.data = &[];
+                return None;
+            }
+            let (args, rest) = rest.split_at(count * BLOCK_LEN);
+            self.data = rest;
+            return Some(PhandleArg {
+                phandle,
+                provider,
+                args,
+                name,
+            });
+        }
+    }
+}
+
+impl<'de> PhandleArg<'de> {
+    /// 提供者节点的 phandle。
+    pub const fn phandle(&self) -> u32 {
+        self.phandle
+    }
+
+    /// 提供者节点。
+    pub fn provider(&self) -> &Node<'de> {
+        &self.provider
+    }
+
+    /// 这一项在 `*-names` 属性中的名字。
+    pub const fn name(&self) -> Option<&'de str> {
+        self.name
+    }
+
+    /// 参数数量。
+    pub const fn arg_count(&self) -> usize {
+        self.args.len() / BLOCK_LEN
+    }
+
+    /// 获得第 `i` 个参数。
+    pub fn arg(&self, i: usize) -> Option<u32> {
+        self.args
+            .get(i * BLOCK_LEN..(i + 1) * BLOCK_LEN)
+            .map(|cell| u32::from_be_bytes(cell.try_into().unwrap()))
+    }
+
+    /// 依次访问所有参数。
+    pub fn args(&self) -> impl Iterator<Item = u32> + 'de {
+        self.args
+            .chunks_exact(BLOCK_LEN)
+            .map(|cell| u32::from_be_bytes(cell.try_into().unwrap()))
+    }
+
+    /// 以说明符形式获得所有参数。
+    pub(super) fn specifier(&self) -> Option<Specifier> {
+        Specifier::from_bytes(self.args)
+    }
+}
+
+/// 固定容量的说明符，保存经过 nexus 映射后的参数。
+#[derive(Clone, Copy, Debug)]
+pub(super) struct Specifier {
+    cells: [u32; SPECIFIER_MAX_CELLS],
+    len: usize,
+}
+
+impl Specifier {
+    /// 从大端序的单元构造说明符，单元数超过容量时返回 `None`。
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        let len = data.len() / BLOCK_LEN;
+        if len > SPECIFIER_MAX_CELLS {
+            return None;
+        }
+        let mut cells = [0; SPECIFIER_MAX_CELLS];
+        for (cell, bytes) in cells.iter_mut().zip(data.chunks_exact(BLOCK_LEN)) {
+            *cell = u32::from_be_bytes(bytes.try_into().unwrap());
+        }
+        Some(Self { cells, len })
+    }
+
+    /// 获得第 `i` 个单元。
+    pub fn get(&self, i: usize) -> Option<u32> {
+        self.as_slice().get(i).copied()
+    }
+
+    /// 以切片形式访问所有单元。
+    pub fn as_slice(&self) -> &[u32] {
+        &self.cells[..self.len]
+    }
+}
+
+/// 找到名为 `<kind><suffix>` 的属性，返回其值。
+fn kind_prop<'de>(node: &Node<'de>, kind: &str, suffix: &str) -> Option<&'de [u8]> {
+    node.props()
+        .find(|prop| prop.get_name().strip_suffix(suffix) == Some(kind))
+        .map(|prop| prop.deserialize::<&[u8]>())
+}
+
+/// 读取节点的 `#<kind>-cells` 属性，不存在时视为 0。
+fn kind_cells(node: &Node, kind: &str) -> usize {
+    node.props()
+        .find(|prop| {
+            let name = prop.get_name();
+            name.strip_prefix('#')
+                .and_then(|name| name.strip_suffix("-cells"))
+                == Some(kind)
+        })
+        .map_or(0, |prop| prop.deserialize::<u32>()) as usize
+}
+
+/// 按 nexus 节点的 `<kind>-map`、`<kind>-map-mask` 和 `<kind>-map-pass-thru` 属性映射说明符，
+/// 直到到达不含映射表的节点。
+///
+/// 映射表中找不到匹配项或映射表格式错误时返回 `None`。
+pub(super) fn map_nexus<'de>(
+    mut node: Node<'de>,
+    mut spec: Specifier,
+    kind: &str,
+) -> Option<(Node<'de>, Specifier)> {
+    for _ in 0..NEXUS_MAX_DEPTH {
+        let Some(mut map) = kind_prop(&node, kind, "-map") else {
+            return Some((node, spec));
+        };
+        let mask = kind_prop(&node, kind, "-map-mask").and_then(Specifier::from_bytes);
+        let pass = kind_prop(&node, kind, "-map-pass-thru").and_then(Specifier::from_bytes);
+        let mut masked = spec;
+        for (i, cell) in masked.cells[..masked.len].iter_mut().enumerate() {
+            *cell &= mask.and_then(|mask| mask.get(i)).unwrap_or(!0);
+        }
+        let child_len = spec.len * BLOCK_LEN;
+        // 映射表中相邻的项通常指向同一个父节点，缓存上一次查找的结果
+        let mut cached: Option<(u32, Node<'de>, usize)> = None;
+        let (parent, parent_spec) = loop {
+            if map.len() < child_len + BLOCK_LEN {
+                return None;
+            }
+            let (child, rest) = map.split_at(child_len);
+            let (phandle, rest) = rest.split_at(BLOCK_LEN);
+            let phandle = u32::from_be_bytes(phandle.try_into().unwrap());
+            let (parent, parent_cells) = match cached {
+                Some((cached_phandle, ref parent, cells)) if cached_phandle == phandle => {
+                    (parent.clone(), cells)
+                }
+                _ => {
+                    let parent = node.find_by_phandle(phandle)?;
+                    let cells = kind_cells(&parent, kind);
+                    cached = Some((phandle, parent.clone(), cells));
+                    (parent, cells)
+                }
+            };
+            if rest.len() < parent_cells * BLOCK_LEN {
+                return None;
+            }
+            let (parent_spec, rest) = rest.split_at(parent_cells * BLOCK_LEN);
+            map = rest;
+            if Specifier::from_bytes(child)?.as_slice() == masked.as_slice() {
+                break (parent, Specifier::from_bytes(parent_spec)?);
+            }
+        };
+        let mut next = parent_spec;
+        for (i, cell) in next.cells[..next.len].iter_mut().enumerate() {
+            let pass = pass.and_then(|pass| pass.get(i)).unwrap_or(0);
+            *cell = (*cell & !pass) | (spec.get(i).unwrap_or(0) & pass);
+        }
+        node = parent;
+        spec = next;
+    }
+    None
+}
+
+impl Debug for PhandleArg<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if let Some(name) = self.name {
+            write!(f, "{name:?}: ")?;
+        }
+        write!(f, "<{:#x}", self.phandle)?;
+        for arg in self.args() {
+            write!(f, " {arg:#x}")?;
+        }
+        write!(f, ">")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{buildin::Node, from_raw_mut, Dtb, DtbPtr};
+
+    const RAW_DEVICE_TREE: &[u8] = include_bytes!("../../examples/hifive-unmatched-a00.dtb");
+    const BUFFER_SIZE: usize = RAW_DEVICE_TREE.len();
+    #[repr(align(8))]
+    struct AlignedBuffer {
+        pub data: [u8; RAW_DEVICE_TREE.len()],
+    }
+    #[test]
+    fn test_phandle_args() {
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE],
+        });
+        aligned_data.data[..BUFFER_SIZE].clone_from_slice(RAW_DEVICE_TREE);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        let ethernet = node.find("/soc/ethernet@10090000").unwrap();
+        let clocks = ethernet.clocks().unwrap();
+        assert_eq!(clocks.iter().count(), 2);
+        let hclk = clocks.by_name("hclk").unwrap();
+        assert_eq!(hclk.phandle(), 1);
+        assert_eq!(hclk.provider().phandle(), Some(1));
+        assert_eq!(hclk.args().collect::<Vec<_>>(), [2]);
+        assert!(clocks.by_name("tx").is_none());
+
+        let prci = node.find("/soc/clock-controller@10000000").unwrap();
+        // 提供者 `#clock-cells = <0>`
+        let parents = prci.clocks().unwrap();
+        let parents = parents.iter().collect::<Vec<_>>();
+        assert_eq!(parents.len(), 2);
+        assert_eq!(parents[1].phandle(), 9);
+        assert_eq!(parents[1].arg_count(), 0);
+        assert_eq!(parents[1].name(), None);
+
+        let resets = prci.resets().unwrap();
+        let gemgxl = resets.by_name("gemgxl_reset").unwrap();
+        assert_eq!(gemgxl.arg(0), Some(5));
+        assert_eq!(gemgxl.arg(1), None);
+        assert!(prci.dmas().is_none());
+    }
+}
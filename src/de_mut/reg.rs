@@ -1,4 +1,5 @@
-﻿use super::{PropCursor, RefDtb, ValueCursor, BLOCK_LEN};
+﻿use super::cell_array::{CellArray, CellArrayIter};
+use super::{PropCursor, RefDtb, ValueCursor};
 use core::{fmt::Debug, ops::Range};
 use serde::Deserialize;
 
@@ -12,10 +13,7 @@ pub(super) struct Inner<'de> {
 }
 
 /// 地址段迭代器。
-pub struct RegIter<'de> {
-    data: &'de [u8],
-    config: RegConfig,
-}
+pub struct RegIter<'de>(CellArrayIter<'de, 2>);
 
 #[derive(Clone, Debug)]
 pub struct RegRegion(pub Range<usize>);
@@ -58,11 +56,16 @@ impl<'de> Deserialize<'de> for Reg<'_> {
 }
 
 impl Reg<'_> {
-    pub fn iter(&self) -> RegIter {
-        RegIter {
-            data: self.0.cursor.data_on(self.0.dtb),
-            config: self.0.reg,
-        }
+    pub fn iter(&self) -> RegIter<'_> {
+        RegIter(CellArrayIter::new(
+            self.0.cursor.data_on(self.0.dtb),
+            [self.0.reg.address_cells, self.0.reg.size_cells],
+        ))
+    }
+
+    /// 属性值的原始单元数组。
+    pub fn cells(&self) -> CellArray<'_> {
+        CellArray::from_cursor(self.0.dtb, self.0.cursor)
     }
 }
 
@@ -85,34 +88,7 @@ impl Iterator for RegIter<'_> {
     type Item = RegRegion;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let len = BLOCK_LEN * (self.config.address_cells + self.config.size_cells);
-        if self.data.len() >= len {
-            let (current_block, data) = self.data.split_at(len);
-            self.data = data;
-            let mut base = 0;
-            let mut len = 0;
-            let mut block_id = 0;
-            for _ in 0..self.config.address_cells {
-                base = (base << 32)
-                    | u32::from_be_bytes(
-                        current_block[block_id * 4..(block_id + 1) * 4]
-                            .try_into()
-                            .unwrap(),
-                    ) as usize;
-                block_id += 1;
-            }
-            for _ in 0..self.config.size_cells {
-                len = (len << 32)
-                    | u32::from_be_bytes(
-                        current_block[block_id * 4..(block_id + 1) * 4]
-                            .try_into()
-                            .unwrap(),
-                    ) as usize;
-                block_id += 1;
-            }
-            Some(RegRegion(base..base + len))
-        } else {
-            None
-        }
+        let [base, len] = self.0.next()?;
+        Some(RegRegion(base as usize..(base + len) as usize))
     }
 }
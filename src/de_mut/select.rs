@@ -0,0 +1,292 @@
+use super::descendants::Descendants;
+use super::node::Node;
+
+/// 查询能跟踪的最大深度，更深的节点不会被选中。
+const SELECT_MAX_DEPTH: usize = 32;
+/// 查询的最大层数。
+const SELECT_MAX_STEPS: usize = 63;
+
+/// 选择器查询结果的迭代器。
+///
+/// 查询语句由 `/` 分隔的若干层组成，每层匹配一级节点：
+///
+/// - 以 `/` 开头的查询从根节点开始，否则从调用 [`Node::select`] 的节点开始；
+/// - `//` 表示中间可以间隔任意多级节点，如 `//cpu@*`；
+/// - 节点名中可以使用通配符 `*` 和 `?`，不含 `@` 的名字只和节点名中 `@` 之前的部分比较；
+/// - 每层可以跟若干个属性条件：`[prop]` 要求属性存在，
+///   `[prop="v"]` 要求属性值恰好是字符串 `v`，`[prop~="v"]` 要求属性的字符串列表包含 `v`。
+///
+/// 例如 `/soc/*[compatible~="ns16550a"][status="okay"]`。
+/// 查询过程在结构块上直接遍历，不分配内存；格式错误的层不匹配任何节点。
+pub struct Select<'de, 'q> {
+    query: &'q str,
+    steps: usize,
+    iter: Descendants<'de>,
+    /// 每一深度上已经匹配的层数集合。
+    states: [u64; SELECT_MAX_DEPTH],
+}
+
+impl<'de> Node<'de> {
+    /// 按选择器查询节点，查询语言见 [`Select`]。
+    pub fn select<'q>(&self, query: &'q str) -> Select<'de, 'q> {
+        let (start, query) = match query.strip_prefix('/') {
+            Some(query) => (self.root(), query),
+            None => (self.clone(), query),
+        };
+        Select {
+            query,
+            steps: Steps(query).count(),
+            iter: start.descendants(),
+            states: [0; SELECT_MAX_DEPTH],
+        }
+    }
+}
+
+impl<'de> Iterator for Select<'de, '_> {
+    type Item = Node<'de>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.steps > SELECT_MAX_STEPS {
+            return None;
+        }
+        loop {
+            let (depth, node) = self.iter.next()?;
+            if depth >= SELECT_MAX_DEPTH {
+                self.iter.skip_subtree();
+                continue;
+            }
+            let states = if depth == 0 {
+                1
+            } else {
+                let parent = self.states[depth - 1];
+                let mut states = 0;
+                for (k, step) in Steps(self.query).enumerate() {
+                    if parent & (1 << k) == 0 {
+                        continue;
+                    }
+                    if step.descend {
+                        states |= 1 << k;
+                    }
+                    if step.matches(&node) {
+                        states |= 1 << (k + 1);
+                    }
+                }
+                states
+            };
+            self.states[depth] = states;
+            // 子孙节点不可能再匹配
+            if states == 0 {
+                self.iter.skip_subtree();
+                continue;
+            }
+            if states & (1 << self.steps) != 0 {
+                return Some(node);
+            }
+        }
+    }
+}
+
+/// 查询中的一层。
+struct Step<'q> {
+    /// 前面是 `//`。
+    descend: bool,
+    segment: &'q str,
+}
+
+/// 切分查询的各层，引号和方括号内的 `/` 不作为分隔符。
+struct Steps<'q>(&'q str);
+
+impl<'q> Iterator for Steps<'q> {
+    type Item = Step<'q>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut descend = false;
+        while let Some(rest) = self.0.strip_prefix('/') {
+            descend = true;
+            self.0 = rest;
+        }
+        if self.0.is_empty() {
+            return None;
+        }
+        let end = find_outside(self.0, b'/').unwrap_or(self.0.len());
+        let (segment, rest) = self.0.split_at(end);
+        self.0 = rest.strip_prefix('/').unwrap_or(rest);
+        Some(Step { descend, segment })
+    }
+}
+
+impl Step<'_> {
+    /// 节点是否满足这一层的名字和所有属性条件。
+    fn matches(&self, node: &Node) -> bool {
+        let end = self.segment.find('[').unwrap_or(self.segment.len());
+        let (pattern, mut predicates) = self.segment.split_at(end);
+        let name = node.name();
+        let name = if pattern.contains('@') {
+            name
+        } else {
+            name.split('@').next().unwrap_or(name)
+        };
+        if !pattern.is_empty() && !glob(pattern.as_bytes(), name.as_bytes()) {
+            return false;
+        }
+        while !predicates.is_empty() {
+            let Some(inner) = predicates.strip_prefix('[') else {
+                return false;
+            };
+            let Some(end) = find_outside(inner, b']') else {
+                return false;
+            };
+            if !matches_predicate(node, &inner[..end]) {
+                return false;
+            }
+            predicates = &inner[end + 1..];
+        }
+        true
+    }
+}
+
+/// 判断节点是否满足一个属性条件。
+fn matches_predicate(node: &Node, predicate: &str) -> bool {
+    let Some(eq) = find_outside(predicate, b'=') else {
+        return node.get_prop(predicate).is_some();
+    };
+    let (name, contains) = match predicate[..eq].strip_suffix('~') {
+        Some(name) => (name, true),
+        None => (&predicate[..eq], false),
+    };
+    let value = &predicate[eq + 1..];
+    let value = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value);
+    let Some(prop) = node.get_prop(name) else {
+        return false;
+    };
+    let data = prop.deserialize::<&[u8]>();
+    if contains {
+        data.split(|b| *b == b'\0').any(|s| s == value.as_bytes())
+    } else {
+        data.strip_suffix(b"\0") == Some(value.as_bytes())
+    }
+}
+
+/// 找到引号和方括号以外第一个 `target` 的位置。
+fn find_outside(s: &str, target: u8) -> Option<usize> {
+    let mut quoted = false;
+    let mut depth = 0usize;
+    for (i, b) in s.bytes().enumerate() {
+        match b {
+            b'"' => quoted = !quoted,
+            _ if quoted => {}
+            _ if b == target && depth == 0 => return Some(i),
+            b'[' => depth += 1,
+            b']' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// 通配符匹配，`*` 匹配任意多个字符，`?` 匹配一个字符。
+fn glob(pattern: &[u8], name: &[u8]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    // 上一个 `*` 的位置，以及它开始匹配的位置
+    let mut star = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == b'?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((sp, sn)) => {
+                    p = sp + 1;
+                    n = sn + 1;
+                    star = Some((sp, sn + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == b'*')
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{buildin::Node, from_raw_mut, Dtb, DtbPtr};
+
+    const RAW_DEVICE_TREE: &[u8] = include_bytes!("../../examples/hifive-unmatched-a00.dtb");
+    const BUFFER_SIZE: usize = RAW_DEVICE_TREE.len();
+    #[test]
+    fn test_select() {
+        #[repr(align(8))]
+        struct AlignedBuffer {
+            pub data: [u8; RAW_DEVICE_TREE.len()],
+        }
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE],
+        });
+        aligned_data.data[..BUFFER_SIZE].clone_from_slice(RAW_DEVICE_TREE);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        assert_eq!(node.select("/").count(), 1);
+        assert_eq!(node.select("/soc/*").count(), 17);
+        assert_eq!(node.select("/soc/serial").count(), 2);
+        assert_eq!(node.select("/soc/spi@1004*").count(), 2);
+        assert_eq!(node.select("/soc/spi@1004?000").count(), 2);
+        assert_eq!(node.select("/soc/*[status=\"okay\"]").count(), 11);
+        assert_eq!(node.select("/soc/*[status=disabled]").count(), 2);
+        assert_eq!(
+            node.select("/soc/*[compatible~=\"sifive,uart0\"][status=\"okay\"]")
+                .count(),
+            2
+        );
+        // 字符串列表不能用 = 匹配其中一项
+        assert_eq!(
+            node.select("/soc/*[compatible=\"sifive,uart0\"]").count(),
+            0
+        );
+        assert_eq!(node.select("//cpu@*").count(), 5);
+        assert_eq!(node.select("/cpus//interrupt-controller").count(), 5);
+        assert_eq!(
+            node.select("//interrupt-controller[#interrupt-cells]")
+                .count(),
+            6
+        );
+        assert_eq!(node.select("//*[gpio-controller]").count(), 1);
+        // 引号中的 `/` 和 `]` 不是分隔符
+        assert_eq!(
+            node.select("/aliases[serial0=\"/soc/serial@10010000\"]")
+                .count(),
+            1
+        );
+        assert_eq!(node.select("/aliases[serial0=\"a]b\"]").count(), 0);
+        // 相对查询，只从子节点开始逐层匹配
+        let soc = node.find("/soc").unwrap();
+        assert_eq!(soc.select("ethernet/ethernet-phy@0").count(), 1);
+        assert_eq!(soc.select("*/ethernet-phy@*").count(), 1);
+        // ethernet-phy 不是 /soc 的子节点
+        assert_eq!(soc.select("ethernet-phy@*").count(), 0);
+        // 以 `/` 开头的查询总是从根节点开始，与调用的节点无关
+        assert_eq!(soc.select("//ethernet-phy@*").count(), 1);
+        assert_eq!(soc.select("//cpu@*").count(), 5);
+        assert!(soc
+            .select("//cpu@*")
+            .all(|cpu| cpu.parent().unwrap().name() == "cpus"));
+        // 格式错误
+        assert_eq!(node.select("/soc/*[status").count(), 0);
+        assert_eq!(node.select("/soc/*[status]x").count(), 0);
+
+        let mut uart = node.select("//serial@*[status=\"okay\"]");
+        assert!(uart.next().unwrap().path() == "/soc/serial@10010000");
+        assert!(uart.next().unwrap().path() == "/soc/serial@10011000");
+        assert!(uart.next().is_none());
+    }
+}
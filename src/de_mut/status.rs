@@ -0,0 +1,177 @@
+use super::node::{Node, NodeItem};
+use super::node_seq::{NodeSeq, NodeSeqItem};
+use super::{ValueCursor, ValueDeserializer};
+use core::fmt::{Debug, Display};
+use serde::{de, Deserialize};
+
+/// 设备状态，对应 `status` 属性。
+///
+/// 没有 `status` 属性的节点视为 [`Status::Okay`]。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status<'de> {
+    /// 设备可以使用，`"okay"` 或旧式的 `"ok"`。
+    Okay,
+    /// 设备目前不可用，但将来可能变为可用。
+    Disabled,
+    /// 设备可以使用，但不应被使用，通常由其他软件组件控制。
+    Reserved,
+    /// 设备不可用，检测到了严重错误。
+    Fail,
+    /// 设备不可用，`-` 之后的部分描述了具体错误。
+    FailSss(&'de str),
+}
+
+impl<'de> Status<'de> {
+    /// 从属性值解析设备状态，无法识别时返回 `None`。
+    pub fn from_bytes(data: &'de [u8]) -> Option<Self> {
+        let len = data.iter().position(|b| *b == b'\0').unwrap_or(data.len());
+        match core::str::from_utf8(&data[..len]).ok()? {
+            "okay" | "ok" => Some(Self::Okay),
+            "disabled" => Some(Self::Disabled),
+            "reserved" => Some(Self::Reserved),
+            "fail" => Some(Self::Fail),
+            s => s.strip_prefix("fail-").map(Self::FailSss),
+        }
+    }
+
+    /// 设备是否可用。
+    pub const fn is_okay(&self) -> bool {
+        matches!(self, Self::Okay)
+    }
+}
+
+impl Display for Status<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Okay => write!(f, "okay"),
+            Self::Disabled => write!(f, "disabled"),
+            Self::Reserved => write!(f, "reserved"),
+            Self::Fail => write!(f, "fail"),
+            Self::FailSss(sss) => write!(f, "fail-{sss}"),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Status<'_> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value_deserialzer = ValueDeserializer::deserialize(deserializer)?;
+        let data = match value_deserialzer.cursor {
+            ValueCursor::Prop(_, cursor) => cursor.data_on(value_deserialzer.dtb),
+            _ => {
+                unreachable!("Status Deserialize should only be called by prop cursor")
+            }
+        };
+        Status::from_bytes(data)
+            .ok_or_else(|| de::Error::invalid_value(de::Unexpected::Bytes(data), &"device status"))
+    }
+}
+
+impl<'de> Node<'de> {
+    /// 获得节点的设备状态。
+    ///
+    /// 没有 `status` 属性时返回 [`Status::Okay`]，无法识别时返回 `None`。
+    pub fn status(&self) -> Option<Status<'de>> {
+        match self.get_prop("status") {
+            Some(prop) => Status::from_bytes(prop.deserialize::<&[u8]>()),
+            None => Some(Status::Okay),
+        }
+    }
+
+    /// 如果设备可用，返回 `true`。
+    ///
+    /// 只有 `status` 属性不存在、为 `"okay"` 或 `"ok"` 时设备可用。
+    pub fn is_enabled(&self) -> bool {
+        matches!(self.status(), Some(Status::Okay))
+    }
+
+    /// 获得可用子节点的迭代器。
+    pub fn enabled_nodes<'b>(&'b self) -> impl Iterator<Item = NodeItem<'de>> + 'b {
+        self.nodes().filter(NodeItem::is_enabled)
+    }
+}
+
+impl NodeItem<'_> {
+    /// 如果节点对应的设备可用，返回 `true`。
+    pub fn is_enabled(&self) -> bool {
+        self.deserialize::<Node>().is_enabled()
+    }
+}
+
+impl NodeSeqItem<'_> {
+    /// 如果节点对应的设备可用，返回 `true`。
+    pub fn is_enabled(&self) -> bool {
+        self.deserialize::<Node>().is_enabled()
+    }
+}
+
+impl<'de> NodeSeq<'de> {
+    /// 获得可用节点的迭代器。
+    pub fn iter_enabled<'b>(&'b self) -> impl Iterator<Item = NodeSeqItem<'de>> + 'b {
+        self.iter().filter(NodeSeqItem::is_enabled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buildin::{Node, NodeSeq, Status};
+    use crate::{from_raw_mut, Dtb, DtbPtr};
+    use serde_derive::Deserialize;
+
+    const RAW_DEVICE_TREE: &[u8] = include_bytes!("../../examples/hifive-unmatched-a00.dtb");
+    const BUFFER_SIZE: usize = RAW_DEVICE_TREE.len();
+    #[repr(align(8))]
+    struct AlignedBuffer {
+        pub data: [u8; RAW_DEVICE_TREE.len()],
+    }
+    #[derive(Deserialize)]
+    struct Tree<'a> {
+        soc: Soc<'a>,
+    }
+    #[derive(Deserialize)]
+    struct Soc<'a> {
+        spi: NodeSeq<'a>,
+    }
+    #[derive(Deserialize)]
+    struct Spi<'a> {
+        status: Status<'a>,
+    }
+    #[test]
+    fn test_status() {
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE],
+        });
+        aligned_data.data[..BUFFER_SIZE].clone_from_slice(RAW_DEVICE_TREE);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        let soc = node.find("/soc").unwrap();
+        assert_eq!(soc.status(), Some(Status::Okay));
+        let i2c1 = node.find("/soc/i2c@10031000").unwrap();
+        assert_eq!(i2c1.status(), Some(Status::Disabled));
+        assert!(!i2c1.is_enabled());
+        assert_eq!(soc.nodes().count(), 17);
+        assert_eq!(soc.enabled_nodes().count(), 15);
+
+        let t: Tree = node.deserialize();
+        let status = t
+            .soc
+            .spi
+            .iter()
+            .map(|spi| spi.deserialize::<Spi>().status)
+            .collect::<Vec<_>>();
+        assert_eq!(status, [Status::Okay, Status::Disabled, Status::Okay]);
+        assert_eq!(t.soc.spi.iter_enabled().count(), 2);
+
+        assert_eq!(Status::from_bytes(b"ok\0"), Some(Status::Okay));
+        assert_eq!(
+            Status::from_bytes(b"fail-overheat\0"),
+            Some(Status::FailSss("overheat"))
+        );
+        assert_eq!(Status::from_bytes(b"broken\0"), None);
+    }
+}
@@ -1,4 +1,5 @@
-﻿use super::{PropCursor, RefDtb, ValueCursor};
+use super::{PropCursor, RefDtb, ValueCursor, BLOCK_LEN};
+use crate::error::Error as DtError;
 use core::fmt::Debug;
 use serde::Deserialize;
 
@@ -8,12 +9,9 @@ use serde::Deserialize;
 /// 这样的一条属性会被编译为两个连续的 '\0' 结尾字符串。
 /// `StrSeq` 可以自动将它们分开。
 ///
-/// `iter` 方法会创建一个迭代器，用于依次访问这些字符串。
-/// 根据实现，迭代器会以从右到左的顺序返回这些字符串。
-///
-/// 构建时，所有字符串被遍历，所有分隔位置被记录下来。
-/// 这需要修改 DTB 上字符串所在位置的内存，因此需要这块内存的写权限。
-/// 如果要以其他方式解析 DTB，先将 `StrSeq` 释放，否则可能引发错误。
+/// `iter` 方法会创建一个迭代器，按从左到右的顺序依次访问这些字符串。
+/// 字符串在访问时才做 UTF-8 检查，不会修改 DTB 的内存。
+/// 也可以用 `get` 按序号访问，以便将 `reg-names`、`clock-names` 等属性与对应的属性按序号关联。
 pub struct StrSeq<'de>(Inner<'de>);
 
 pub(super) struct Inner<'de> {
@@ -22,10 +20,18 @@ pub(super) struct Inner<'de> {
 }
 
 /// '\0' 分隔字符串组迭代器。
+///
+/// 遇到不是合法 UTF-8 的字符串时，迭代结束，之后的字符串也不再访问。
 pub struct StrSeqIter<'de> {
     data: &'de [u8],
 }
 
+/// 检查 UTF-8 的 '\0' 分隔字符串组迭代器。
+pub struct StrSeqTryIter<'de> {
+    data: &'de [u8],
+    file_index: usize,
+}
+
 impl<'de> Deserialize<'de> for StrSeq<'_> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -49,25 +55,68 @@ impl<'de> Deserialize<'de> for StrSeq<'_> {
 
 impl<'de> StrSeq<'de> {
     /// 构造一个可访问每个字符串的迭代器。
+    ///
+    /// 迭代在第一个不是合法 UTF-8 的字符串处结束，此时访问到的字符串少于 [`StrSeq::len`]；
+    /// 需要访问全部字符串时使用 [`StrSeq::try_iter`]。
     pub fn iter<'b>(&'b self) -> StrSeqIter<'de> {
-        StrSeqIter {
-            data: self.0.cursor.data_on(self.0.dtb),
+        StrSeqIter { data: self.data() }
+    }
+
+    /// 构造一个可访问每个字符串的迭代器，不是合法 UTF-8 的字符串将产生错误。
+    pub fn try_iter<'b>(&'b self) -> StrSeqTryIter<'de> {
+        StrSeqTryIter {
+            data: self.data(),
+            // 跳过属性标签、长度和名字偏移
+            file_index: self.0.cursor.file_index_on(self.0.dtb) + 3 * BLOCK_LEN,
         }
     }
+
+    /// 字符串的数量，包括不是合法 UTF-8 的字符串。
+    pub fn len(&self) -> usize {
+        Split(self.data()).count()
+    }
+
+    /// 如果没有任何字符串，返回 `true`。
+    pub fn is_empty(&self) -> bool {
+        Split(self.data()).next().is_none()
+    }
+
+    /// 获得序号为 `index` 的字符串。
+    ///
+    /// 序号超出范围或字符串不是合法 UTF-8 时返回 `None`。
+    pub fn get(&self, index: usize) -> Option<&'de str> {
+        core::str::from_utf8(Split(self.data()).nth(index)?).ok()
+    }
+
+    /// 如果包含字符串 `s`，返回 `true`。
+    pub fn contains(&self, s: &str) -> bool {
+        self.position(s).is_some()
+    }
+
+    /// 获得字符串 `s` 第一次出现的序号。
+    pub fn position(&self, s: &str) -> Option<usize> {
+        Split(self.data()).position(|item| item == s.as_bytes())
+    }
+
+    #[inline]
+    fn data(&self) -> &'de [u8] {
+        self.0.cursor.data_on(self.0.dtb)
+    }
 }
 
 impl Debug for StrSeq<'_> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        let mut iter = self.iter();
-        if let Some(s) = iter.next() {
-            write!(f, "[\"{s}\"",)?;
-            for s in iter {
-                write!(f, ", \"{s}\"")?;
+        write!(f, "[")?;
+        for (i, s) in self.try_iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            match s {
+                Ok(s) => write!(f, "\"{s}\"")?,
+                Err(_) => write!(f, "<invalid UTF-8>")?,
             }
-            write!(f, "]")
-        } else {
-            write!(f, "[]")
         }
+        write!(f, "]")
     }
 }
 
@@ -75,18 +124,137 @@ impl<'de> Iterator for StrSeqIter<'de> {
     type Item = &'de str;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.data.is_empty() {
-            None
-        } else {
-            let pos = self
-                .data
-                .iter()
-                .position(|&x| x == b'\0')
-                .unwrap_or(self.data.len());
-            let (a, b) = self.data.split_at(pos + 1);
-            self.data = b;
-            // Remove \0 at end
-            Some(unsafe { core::str::from_utf8_unchecked(&a[..a.len() - 1]) })
+        let mut split = Split(self.data);
+        let item = split.next()?;
+        match core::str::from_utf8(item) {
+            Ok(s) => {
+                self.data = split.0;
+                Some(s)
+            }
+            Err(_) => {
+                self.data = &[];
+                None
+            }
         }
     }
 }
+
+impl<'de> Iterator for StrSeqTryIter<'de> {
+    type Item = Result<&'de str, DtError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut split = Split(self.data);
+        let item = split.next()?;
+        let file_index = self.file_index;
+        self.file_index += self.data.len() - split.0.len();
+        self.data = split.0;
+        Some(core::str::from_utf8(item).map_err(|e| DtError::utf8(e, file_index)))
+    }
+}
+
+/// 按 '\0' 切分字节串，最后一个字符串可以没有结尾的 '\0'。
+struct Split<'de>(&'de [u8]);
+
+impl<'de> Iterator for Split<'de> {
+    type Item = &'de [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.0.is_empty() {
+            return None;
+        }
+        match self.0.iter().position(|&x| x == b'\0') {
+            Some(pos) => {
+                let (item, rest) = self.0.split_at(pos);
+                self.0 = &rest[1..];
+                Some(item)
+            }
+            None => Some(core::mem::take(&mut self.0)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buildin::{Node, StrSeq};
+    use crate::{from_raw_mut, Dtb, DtbPtr};
+
+    const RAW_DEVICE_TREE: &[u8] = include_bytes!("../../examples/hifive-unmatched-a00.dtb");
+    const BUFFER_SIZE: usize = RAW_DEVICE_TREE.len();
+    #[test]
+    fn test_str_seq() {
+        #[repr(align(8))]
+        struct AlignedBuffer {
+            pub data: [u8; RAW_DEVICE_TREE.len()],
+        }
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE],
+        });
+        aligned_data.data[..BUFFER_SIZE].clone_from_slice(RAW_DEVICE_TREE);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        let cpu = node.find("/cpus/cpu@0").unwrap();
+        let compatible = cpu.get_prop("compatible").unwrap().deserialize::<StrSeq>();
+        assert_eq!(compatible.len(), 2);
+        assert!(!compatible.is_empty());
+        assert_eq!(compatible.get(0), Some("sifive,bullet0"));
+        assert_eq!(compatible.get(1), Some("riscv"));
+        assert_eq!(compatible.get(2), None);
+        assert!(compatible.contains("riscv"));
+        assert!(!compatible.contains("risc"));
+        assert_eq!(compatible.position("riscv"), Some(1));
+        assert_eq!(compatible.iter().count(), 2);
+        assert!(compatible.try_iter().all(|s| s.is_ok()));
+
+        let clock_names = node
+            .find("/soc/ethernet@10090000")
+            .unwrap()
+            .get_prop("clock-names")
+            .unwrap()
+            .deserialize::<StrSeq>();
+        assert_eq!(clock_names.position("hclk"), Some(1));
+    }
+    #[test]
+    fn test_str_seq_invalid_utf8() {
+        #[repr(align(8))]
+        struct AlignedBuffer {
+            pub data: [u8; RAW_DEVICE_TREE.len()],
+        }
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE],
+        });
+        aligned_data.data[..BUFFER_SIZE].clone_from_slice(RAW_DEVICE_TREE);
+        let mut slice = aligned_data.data.to_vec();
+        // 把 /cpus/cpu@0 的 `compatible` 中第一个字符串改坏
+        let pattern = b"sifive,bullet0\0riscv\0";
+        let pos = slice
+            .windows(pattern.len())
+            .position(|window| window == pattern)
+            .unwrap();
+        slice[pos] = 0xff;
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        let cpu = node.find("/cpus/cpu@0").unwrap();
+        let compatible = cpu.get_prop("compatible").unwrap().deserialize::<StrSeq>();
+        let mut iter = compatible.try_iter();
+        assert!(iter.next().unwrap().is_err());
+        // 错误不影响后续的字符串
+        assert_eq!(iter.next().unwrap().unwrap(), "riscv");
+        assert!(iter.next().is_none());
+
+        assert_eq!(compatible.len(), 2);
+        assert_eq!(compatible.get(0), None);
+        assert_eq!(compatible.get(1), Some("riscv"));
+        assert!(compatible.contains("riscv"));
+        assert!(!compatible.contains("sifive,bullet0"));
+        assert_eq!(compatible.position("riscv"), Some(1));
+        assert!(!compatible.is_empty());
+        // 不检查的迭代器遇到非法字符串就停止
+        assert_eq!(compatible.iter().count(), 0);
+        assert_eq!(format!("{compatible:?}"), "[<invalid UTF-8>, \"riscv\"]");
+    }
+}
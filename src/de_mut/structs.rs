@@ -1,4 +1,10 @@
-﻿use crate::{
+﻿use super::{
+    node::Node,
+    offset::{self, NodeOffset},
+    walk::{self, DtVisitor},
+    DtbIndex,
+};
+use crate::{
     common::{Header, ALIGN},
     error::Error as DtError,
 };
@@ -111,6 +117,7 @@ pub struct Dtb {
     ptr: *const u8,
     pub(super) structure: &'static mut [StructureBlock],
     pub(super) strings: &'static [u8],
+    pub(super) index: Option<DtbIndex<'static>>,
 }
 
 impl From<Dtb> for DtbPtr {
@@ -141,6 +148,7 @@ impl From<DtbPtr> for Dtb {
                     len_structure / core::mem::size_of::<StructureBlock>(),
                 ),
                 strings: core::slice::from_raw_parts(ptr_strings as _, len_strings),
+                index: None,
             }
         }
     }
@@ -156,6 +164,53 @@ impl Dtb {
     pub fn off_dt_struct(&self) -> usize {
         u32::from_be(unsafe { &*(self.ptr as *const Header) }.off_dt_struct) as _
     }
+
+    /// 获得内存保留块中 `(address, size)` 条目的迭代器。
+    pub fn memreserve(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        let header = unsafe { &*(self.ptr as *const Header) };
+        let off = u32::from_be(header.off_mem_rsvmap) as usize;
+        let total = u32::from_be(header.total_size) as usize;
+        // 首部检查不包括保留块的偏移，越界时视为没有保留块
+        let data = if off > total {
+            &[]
+        } else {
+            unsafe { core::slice::from_raw_parts(self.ptr.add(off), total - off) }
+        };
+        data.chunks_exact(16)
+            .map(|entry| {
+                let (address, size) = entry.split_at(8);
+                (
+                    u64::from_be_bytes(address.try_into().unwrap()),
+                    u64::from_be_bytes(size.try_into().unwrap()),
+                )
+            })
+            .take_while(|entry| *entry != (0, 0))
+    }
 }
 
 pub(super) type RefDtb<'a> = &'a RefCell<Dtb>;
+
+/// 共享的设备树映射上的操作。
+///
+/// 节点借用的是 [`Dtb::share`] 得到的 `RefCell<Dtb>`，因此这些操作以它为接收者。
+pub trait SharedDtb {
+    /// 取回位于 `offset` 的节点。
+    ///
+    /// 如果 `offset` 不是某个节点的位置，返回错误。
+    fn node_at(&self, offset: NodeOffset) -> Result<Node<'_>, DtError>;
+
+    /// 从根节点开始遍历整个设备树，依次回调访问者。
+    ///
+    /// 遍历完成时返回 `true`，被访问者结束时返回 `false`。
+    fn walk<'de>(&'de self, visitor: &mut impl DtVisitor<'de>) -> bool;
+}
+
+impl SharedDtb for RefCell<Dtb> {
+    fn node_at(&self, offset: NodeOffset) -> Result<Node<'_>, DtError> {
+        offset::node_at(self, offset)
+    }
+
+    fn walk<'de>(&'de self, visitor: &mut impl DtVisitor<'de>) -> bool {
+        walk::walk(self, visitor)
+    }
+}
@@ -0,0 +1,110 @@
+use super::cell_array::CellArray;
+use super::node::Node;
+
+/// `ranges` 类属性的映射方向。
+#[derive(Clone, Copy)]
+pub(super) enum RangesDirection {
+    /// 从子地址空间映射到父地址空间。
+    ToParent,
+    /// 从父地址空间映射到子地址空间。
+    ToChild,
+}
+
+impl Node<'_> {
+    /// 将本节点 `reg` 中的总线地址转换为 CPU 视角的物理地址。
+    ///
+    /// 从父节点向上，依次按每一级总线的 `ranges` 属性映射到上一级地址空间。
+    /// `ranges` 为空的总线视为一一映射；
+    /// 某一级总线没有 `ranges` 或无法映射该地址时，返回 `None`。
+    pub fn translate_address(&self, addr: u64) -> Option<u64> {
+        let mut addr = addr;
+        let mut bus = self.parent()?;
+        while let Some(parent) = bus.parent() {
+            addr = bus.map_ranges(
+                "ranges",
+                RangesDirection::ToParent,
+                parent.reg.address_cells,
+                addr,
+            )?;
+            bus = parent;
+        }
+        Some(addr)
+    }
+
+    /// 获得 `reg` 第一项的地址，并转换为 CPU 视角的物理地址。
+    pub fn translated_reg_address(&self) -> Option<u64> {
+        let parent = self.parent()?;
+        let widths = [parent.reg.address_cells, parent.reg.size_cells];
+        let data = self.get_prop("reg")?.deserialize::<&[u8]>();
+        let [addr, _] = CellArray::new(data).entries(widths).ok()?.next()?;
+        self.translate_address(addr)
+    }
+
+    /// 按本节点名为 `name` 的 `ranges` 类属性，在子地址空间和父地址空间之间映射地址。
+    ///
+    /// 属性为空时视为一一映射；没有该属性或地址不在任何一项范围内时返回 `None`。
+    pub(super) fn map_ranges(
+        &self,
+        name: &str,
+        direction: RangesDirection,
+        parent_address_cells: usize,
+        addr: u64,
+    ) -> Option<u64> {
+        let data = self.get_prop(name)?.deserialize::<&[u8]>();
+        if data.is_empty() {
+            return Some(addr);
+        }
+        let widths = [
+            self.reg.address_cells,
+            parent_address_cells,
+            self.reg.size_cells,
+        ];
+        let entries = CellArray::new(data).entries(widths).ok()?;
+        for [child, parent, size] in entries {
+            let (from, to) = match direction {
+                RangesDirection::ToParent => (child, parent),
+                RangesDirection::ToChild => (parent, child),
+            };
+            let offset = addr.wrapping_sub(from);
+            if addr >= from && offset < size {
+                return to.checked_add(offset);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{buildin::Node, from_raw_mut, Dtb, DtbPtr};
+
+    const RAW_DEVICE_TREE: &[u8] = include_bytes!("../../examples/chosen.dtb");
+    const BUFFER_SIZE: usize = RAW_DEVICE_TREE.len();
+    #[repr(align(8))]
+    struct AlignedBuffer {
+        pub data: [u8; RAW_DEVICE_TREE.len()],
+    }
+    #[test]
+    fn test_translate_address() {
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE],
+        });
+        aligned_data.data[..BUFFER_SIZE].clone_from_slice(RAW_DEVICE_TREE);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        let serial = node.find("/soc/serial@0").unwrap();
+        assert_eq!(serial.translate_address(0x10), Some(0x1000_0010));
+        assert_eq!(serial.translated_reg_address(), Some(0x1000_0000));
+        // 超出总线映射的范围
+        assert_eq!(serial.translate_address(0x100_0000), None);
+        // 没有 ranges 的总线不能映射
+        let serial = node.find("/soc/bus@800000/serial@800000").unwrap();
+        assert_eq!(serial.translated_reg_address(), None);
+        // 根节点下的设备不需要转换
+        let memory = node.find("/memory@80000000").unwrap();
+        assert_eq!(memory.translated_reg_address(), Some(0x8000_0000));
+    }
+}
@@ -0,0 +1,318 @@
+use super::node::{Node, NodeItem};
+use super::StrSeq;
+use core::fmt::{Debug, Display};
+
+/// 单元地址最多占用的单元数。
+const UNIT_ADDRESS_MAX_CELLS: usize = 4;
+
+/// 按父节点 `#address-cells` 解析的单元地址。
+///
+/// 节点名 `@` 之后的部分是节点在父总线地址空间中的地址，通常与 `reg` 第一项的地址相同。
+/// 单元地址可以写作一个十六进制数，如 `serial@10010000`，按单元数从低位开始填充；
+/// 也可以用 `,` 分隔，每部分对应一个单元，如 `ethernet@1,0`。
+///
+/// PCI 总线上的单元地址是 `设备号[,功能号]`，格式见 [`UnitAddress::parse_pci`]。
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct UnitAddress {
+    cells: [u32; UNIT_ADDRESS_MAX_CELLS],
+    len: usize,
+    pci: bool,
+}
+
+/// PCI 地址第一个单元中设备号和功能号所在的位。
+const PCI_DEVFN_MASK: u32 = 0xff00;
+
+impl UnitAddress {
+    /// 按 `address_cells` 个单元解析单元地址文本。
+    pub fn parse(text: &str, address_cells: usize) -> Option<Self> {
+        if address_cells == 0 || address_cells > UNIT_ADDRESS_MAX_CELLS || text.is_empty() {
+            return None;
+        }
+        let mut cells = [0; UNIT_ADDRESS_MAX_CELLS];
+        if text.contains(',') {
+            // 每部分对应一个单元
+            let mut parts = text.split(',');
+            for cell in &mut cells[..address_cells] {
+                *cell = parse_hex(parts.next()?, 1)? as _;
+            }
+            if parts.next().is_some() {
+                return None;
+            }
+        } else {
+            // 整体作为一个数，右对齐到各单元
+            parse_hex(text, address_cells)?;
+            let digits = text.trim_start_matches('0').as_bytes();
+            for (cell, chunk) in cells[..address_cells]
+                .iter_mut()
+                .rev()
+                .zip(digits.rchunks(8))
+            {
+                *cell = parse_hex(core::str::from_utf8(chunk).unwrap(), 1)? as _;
+            }
+        }
+        Some(Self {
+            cells,
+            len: address_cells,
+            pci: false,
+        })
+    }
+
+    /// 解析 PCI 总线上的单元地址 `设备号[,功能号]`，如 `ethernet@2,1`。
+    ///
+    /// 结果有 3 个单元，设备号和功能号按 PCI 绑定放在第一个单元的 11 到 15 位和 8 到 10 位，
+    /// 其他位为 0。单元地址中没有总线号。
+    pub fn parse_pci(text: &str) -> Option<Self> {
+        let (device, function) = match text.split_once(',') {
+            Some((device, function)) => (device, parse_hex(function, 1)?),
+            None => (text, 0),
+        };
+        let device = parse_hex(device, 1)?;
+        if device >= 32 || function >= 8 {
+            return None;
+        }
+        let mut cells = [0; UNIT_ADDRESS_MAX_CELLS];
+        cells[0] = ((device << 11) | (function << 8)) as u32;
+        Some(Self {
+            cells,
+            len: 3,
+            pci: true,
+        })
+    }
+
+    /// 从属性值开头的 `address_cells` 个单元构造。
+    fn from_bytes(data: &[u8], address_cells: usize) -> Option<Self> {
+        if address_cells == 0 || address_cells > UNIT_ADDRESS_MAX_CELLS {
+            return None;
+        }
+        let mut cells = [0; UNIT_ADDRESS_MAX_CELLS];
+        for (cell, chunk) in cells
+            .iter_mut()
+            .zip(data.get(..address_cells * 4)?.chunks_exact(4))
+        {
+            *cell = u32::from_be_bytes(chunk.try_into().unwrap());
+        }
+        Some(Self {
+            cells,
+            len: address_cells,
+            pci: false,
+        })
+    }
+
+    /// 各个单元，高位在前。
+    pub fn cells(&self) -> &[u32] {
+        &self.cells[..self.len]
+    }
+
+    /// 拼接为一个 `u64`，超出 64 位时返回 `None`。
+    pub fn as_u64(&self) -> Option<u64> {
+        let (high, low) = self.cells().split_at(self.len.saturating_sub(2));
+        if high.iter().any(|cell| *cell != 0) {
+            return None;
+        }
+        Some(
+            low.iter()
+                .fold(0u64, |acc, cell| (acc << 32) | *cell as u64),
+        )
+    }
+}
+
+impl PartialEq<u64> for UnitAddress {
+    fn eq(&self, other: &u64) -> bool {
+        self.as_u64() == Some(*other)
+    }
+}
+
+impl Display for UnitAddress {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.pci {
+            let (device, function) = ((self.cells[0] >> 11) & 0x1f, (self.cells[0] >> 8) & 0x7);
+            return match function {
+                0 => write!(f, "{device:x}"),
+                _ => write!(f, "{device:x},{function:x}"),
+            };
+        }
+        let mut cells = self.cells();
+        // 与节点名相同，省略高位的零
+        while let [0, rest @ ..] = cells {
+            if rest.is_empty() {
+                break;
+            }
+            cells = rest;
+        }
+        write!(f, "{:x}", cells[0])?;
+        for cell in &cells[1..] {
+            write!(f, "{cell:08x}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Debug for UnitAddress {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "<")?;
+        for (i, cell) in self.cells().iter().enumerate() {
+            if i != 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{cell:#x}")?;
+        }
+        write!(f, ">")
+    }
+}
+
+impl NodeItem<'_> {
+    /// 按父节点的 `#address-cells` 解析单元地址。
+    ///
+    /// 节点有 `reg` 属性时，单元地址必须与 `reg` 第一项的地址一致。
+    /// 父节点是 PCI 总线时按 `设备号[,功能号]` 解析，只比较 `reg` 中的设备号和功能号。
+    /// 没有单元地址、无法解析或与 `reg` 不一致时返回 `None`。
+    pub fn unit_address(&self) -> Option<UnitAddress> {
+        let (_, text) = self.get_parsed_name();
+        let text = text?;
+        let address_cells = self.reg.address_cells;
+        let node = self.to_node();
+        // 只有 3 个单元的地址可能是 PCI 地址，此时才需要查找父节点
+        let pci = address_cells == 3 && node.parent().is_some_and(|bus| is_pci_bus(&bus));
+        let address = match pci {
+            true => UnitAddress::parse_pci(text)?,
+            false => UnitAddress::parse(text, address_cells)?,
+        };
+        let Some(reg) = node.get_prop("reg") else {
+            return Some(address);
+        };
+        let mut first = UnitAddress::from_bytes(reg.deserialize::<&[u8]>(), address_cells)?;
+        if pci {
+            // 总线号和地址空间类型不在单元地址中
+            first.cells[0] &= PCI_DEVFN_MASK;
+            first.pci = true;
+        }
+        (first == address).then_some(address)
+    }
+}
+
+/// 判断节点是否是 PCI 总线。
+fn is_pci_bus(node: &Node) -> bool {
+    node.get_prop("device_type").is_some_and(|prop| {
+        matches!(
+            prop.deserialize::<StrSeq>().iter().next(),
+            Some("pci" | "pciex")
+        )
+    })
+}
+
+/// 解析不超过 `cells` 个单元的十六进制数，只返回低 64 位。
+fn parse_hex(s: &str, cells: usize) -> Option<u64> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digits = s.trim_start_matches('0');
+    if digits.len() > cells * 8 {
+        return None;
+    }
+    let low = &digits[digits.len().saturating_sub(16)..];
+    Some(u64::from_str_radix(low, 16).unwrap_or(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UnitAddress;
+    use crate::{buildin::Node, from_raw_mut, Dtb, DtbPtr};
+
+    const RAW_DEVICE_TREE: &[u8] = include_bytes!("../../examples/hifive-unmatched-a00.dtb");
+    const BUFFER_SIZE: usize = RAW_DEVICE_TREE.len();
+    const RAW_DEVICE_TREE_ARM64: &[u8] = include_bytes!("../../examples/arm64.dtb");
+    const BUFFER_SIZE_ARM64: usize = RAW_DEVICE_TREE_ARM64.len();
+    #[repr(align(8))]
+    struct AlignedBuffer {
+        pub data: [u8; RAW_DEVICE_TREE.len()],
+    }
+    #[test]
+    fn test_unit_address() {
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE],
+        });
+        aligned_data.data[..BUFFER_SIZE].clone_from_slice(RAW_DEVICE_TREE);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        // `#size-cells = <0>` 的 I2C 总线按地址匹配子设备
+        let i2c = node.find("/soc/i2c@10030000").unwrap();
+        let pmic = i2c
+            .nodes()
+            .find(|child| child.unit_address() == Some(UnitAddress::parse("58", 1).unwrap()))
+            .unwrap();
+        assert_eq!(pmic.get_full_name(), "pmic@58");
+        let addresses = i2c
+            .nodes()
+            .map(|child| child.unit_address().unwrap().as_u64().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(addresses, [0x4c, 0x58]);
+
+        let soc = node.find("/soc").unwrap();
+        let serial = soc
+            .nodes()
+            .find(|child| child.get_full_name() == "serial@10010000")
+            .unwrap();
+        let address = serial.unit_address().unwrap();
+        assert_eq!(address.cells(), [0, 0x10010000]);
+        assert_eq!(address, 0x10010000);
+        assert_eq!(format!("{address}"), "10010000");
+        assert_eq!(format!("{address:?}"), "<0x0 0x10010000>");
+        // 没有单元地址
+        let cpus = node.nodes().find(|child| child.get_full_name() == "cpus");
+        assert!(cpus.unwrap().unit_address().is_none());
+
+        let address = UnitAddress::parse("1,0", 3);
+        assert!(address.is_none());
+        let address = UnitAddress::parse("1,2,0", 3).unwrap();
+        assert_eq!(address.cells(), [1, 2, 0]);
+        assert_eq!(address.as_u64(), None);
+        let address = UnitAddress::parse("e00000000", 2).unwrap();
+        assert_eq!(address.cells(), [0xe, 0]);
+        assert_eq!(format!("{address}"), "e00000000");
+        assert!(UnitAddress::parse("100000000", 1).is_none());
+        assert!(UnitAddress::parse("10g", 1).is_none());
+        assert_eq!(UnitAddress::parse("0", 2).unwrap(), 0);
+    }
+    #[test]
+    fn test_unit_address_pci() {
+        #[repr(align(8))]
+        struct AlignedBuffer {
+            pub data: [u8; RAW_DEVICE_TREE_ARM64.len()],
+        }
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE_ARM64],
+        });
+        aligned_data.data[..BUFFER_SIZE_ARM64].clone_from_slice(RAW_DEVICE_TREE_ARM64);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        let pcie = node.find("/pcie@10000000").unwrap();
+        let addresses = pcie
+            .nodes()
+            .map(|child| child.unit_address())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            addresses,
+            [
+                UnitAddress::parse_pci("2,1"),
+                UnitAddress::parse_pci("3"),
+                // 单元地址的设备号与 `reg` 不一致
+                None,
+            ]
+        );
+        let address = addresses[0].unwrap();
+        assert_eq!(address.cells(), [0x1100, 0, 0]);
+        assert_eq!(format!("{address}"), "2,1");
+        assert_eq!(format!("{}", addresses[1].unwrap()), "3");
+        // 普通的 3 单元地址不按 PCI 格式解析
+        assert_ne!(UnitAddress::parse("1100,0,0", 3), addresses[0]);
+        assert!(UnitAddress::parse_pci("20").is_none());
+        assert!(UnitAddress::parse_pci("1,8").is_none());
+        assert!(UnitAddress::parse_pci("1,0,0").is_none());
+    }
+}
@@ -0,0 +1,160 @@
+use super::{BodyCursor, Cursor, RefDtb};
+
+/// 访问者回调的返回值，控制遍历如何继续。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WalkControl {
+    /// 继续遍历。
+    Continue,
+    /// 跳过当前节点剩余的属性和子节点，直接离开当前节点。
+    SkipSubtree,
+    /// 立即结束遍历。
+    Stop,
+}
+
+/// 设备树访问者。
+///
+/// [`SharedDtb::walk`] 按结构块的顺序回调访问者，不需要定义反序列化的类型。
+/// 除非遍历被 [`WalkControl::Stop`] 结束，每次 `enter_node` 都有对应的 `leave_node`。
+///
+/// [`SharedDtb::walk`]: crate::SharedDtb::walk
+pub trait DtVisitor<'de> {
+    /// 进入节点，根节点的名字是空字符串。
+    fn enter_node(&mut self, name: &'de str) -> WalkControl {
+        let _ = name;
+        WalkControl::Continue
+    }
+
+    /// 访问当前节点的一个属性。
+    fn property(&mut self, name: &'de str, value: &'de [u8]) -> WalkControl {
+        let _ = (name, value);
+        WalkControl::Continue
+    }
+
+    /// 离开当前节点。
+    ///
+    /// 返回 [`WalkControl::SkipSubtree`] 与 [`WalkControl::Continue`] 相同。
+    fn leave_node(&mut self) -> WalkControl {
+        WalkControl::Continue
+    }
+}
+
+/// 从根节点开始遍历整个设备树。
+pub(super) fn walk<'de>(dtb: RefDtb<'de>, visitor: &mut impl DtVisitor<'de>) -> bool {
+    let mut cursor = BodyCursor::at(0);
+    let mut depth = 0usize;
+    loop {
+        let control = match cursor.move_on(dtb) {
+            Cursor::Title(c) => {
+                let (name, body) = c.split_on(dtb);
+                cursor = body;
+                depth += 1;
+                Some(visitor.enter_node(name))
+            }
+            Cursor::Prop(c) => {
+                let (name, next) = c.name_on(dtb);
+                cursor = next;
+                Some(visitor.property(name, c.data_on(dtb)))
+            }
+            Cursor::End => {
+                cursor.move_next(dtb);
+                None
+            }
+        };
+        match control {
+            Some(WalkControl::Continue) => continue,
+            Some(WalkControl::SkipSubtree) => cursor.escape_from(dtb),
+            Some(WalkControl::Stop) => return false,
+            // 已经越过节点结束符
+            None => {}
+        }
+        depth -= 1;
+        if visitor.leave_node() == WalkControl::Stop {
+            return false;
+        }
+        if depth == 0 {
+            return true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DtVisitor, WalkControl};
+    use crate::{Dtb, DtbPtr, SharedDtb};
+
+    const RAW_DEVICE_TREE: &[u8] = include_bytes!("../../examples/hifive-unmatched-a00.dtb");
+    const BUFFER_SIZE: usize = RAW_DEVICE_TREE.len();
+    #[repr(align(8))]
+    struct AlignedBuffer {
+        pub data: [u8; RAW_DEVICE_TREE.len()],
+    }
+    /// 统计节点和属性，跳过名为 `skip` 的节点，遇到名为 `stop` 的属性时结束。
+    #[derive(Default)]
+    struct Counter<'de> {
+        nodes: usize,
+        props: usize,
+        depth: usize,
+        skip: Option<&'static str>,
+        stop: Option<&'static str>,
+        model: Option<&'de [u8]>,
+    }
+    impl<'de> DtVisitor<'de> for Counter<'de> {
+        fn enter_node(&mut self, name: &'de str) -> WalkControl {
+            self.nodes += 1;
+            self.depth += 1;
+            if Some(name) == self.skip {
+                WalkControl::SkipSubtree
+            } else {
+                WalkControl::Continue
+            }
+        }
+        fn property(&mut self, name: &'de str, value: &'de [u8]) -> WalkControl {
+            self.props += 1;
+            if name == "model" && self.depth == 1 {
+                self.model = Some(value);
+            }
+            if Some(name) == self.stop {
+                WalkControl::Stop
+            } else {
+                WalkControl::Continue
+            }
+        }
+        fn leave_node(&mut self) -> WalkControl {
+            self.depth -= 1;
+            WalkControl::Continue
+        }
+    }
+    #[test]
+    fn test_walk() {
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE],
+        });
+        aligned_data.data[..BUFFER_SIZE].clone_from_slice(RAW_DEVICE_TREE);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let mut all = Counter::default();
+        assert!(dtb.walk(&mut all));
+        assert_eq!((all.nodes, all.depth), (70, 0));
+        assert_eq!(all.model, Some(&b"SiFive HiFive Unmatched A00\0"[..]));
+
+        // 跳过的节点仍然会离开
+        let mut skipped = Counter {
+            skip: Some("soc"),
+            ..Default::default()
+        };
+        assert!(dtb.walk(&mut skipped));
+        assert_eq!(skipped.depth, 0);
+        // `/soc` 之下还有 40 个节点
+        assert_eq!(skipped.nodes, all.nodes - 40);
+        assert!(skipped.props < all.props);
+
+        let mut stopped = Counter {
+            stop: Some("model"),
+            ..Default::default()
+        };
+        assert!(!dtb.walk(&mut stopped));
+        assert_eq!(stopped.nodes, 1);
+    }
+}
@@ -83,6 +83,9 @@ pub enum ErrorType {
         expected: &'static str,
     },
     Utf8(core::str::Utf8Error),
+    IndexBufferTooSmall {
+        given_length: u32,
+    },
 }
 
 impl Error {
@@ -287,6 +290,13 @@ impl Error {
         }
     }
     #[inline]
+    pub fn index_buffer_too_small(given_length: u32, file_index: usize) -> Error {
+        Error::Typed {
+            error_type: ErrorType::IndexBufferTooSmall { given_length },
+            file_index,
+        }
+    }
+    #[inline]
     pub fn expected_struct_begin() -> Error {
         Error::Typed {
             error_type: ErrorType::ExpectStructBegin,
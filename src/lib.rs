@@ -28,12 +28,15 @@ mod tag;
 mod value;
 
 pub use value::compatible::Compatible;
+pub use value::cpu::{Cpu, CpuIter, CpuTopology, Cpus};
 
 #[doc(inline)]
 pub use de::from_raw;
 
 #[doc(inline)]
-pub use de_mut::{buildin, from_raw_mut, Dtb, DtbPtr};
+pub use de_mut::{
+    buildin, from_raw_mut, DtVisitor, Dtb, DtbIndex, DtbPtr, IndexEntry, SharedDtb, WalkControl,
+};
 
 #[doc(inline)]
 pub use error::Result;
@@ -0,0 +1,559 @@
+use crate::buildin::{Node, Reg, StrSeq};
+use core::ops::Range;
+
+/// Type of an interrupt in a GIC interrupt specifier.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GicIrqKind {
+    /// Shared peripheral interrupt.
+    Spi,
+    /// Private peripheral interrupt.
+    Ppi,
+    /// Extended shared peripheral interrupt, GICv3.1 and later.
+    ExtendedSpi,
+    /// Extended private peripheral interrupt, GICv3.1 and later.
+    ExtendedPpi,
+}
+
+/// A decoded `interrupts` specifier of a GIC.
+///
+/// The specifier has three cells: the type, the number within that type, and the flags.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GicInterrupt {
+    pub kind: GicIrqKind,
+    pub number: u32,
+    pub flags: u32,
+}
+
+/// Version of the GIC architecture.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GicVersion {
+    V2,
+    V3,
+}
+
+/// A GIC node, `arm,gic-400` and friends or `arm,gic-v3`.
+#[derive(Clone)]
+pub struct Gic<'de> {
+    node: Node<'de>,
+    version: GicVersion,
+}
+
+/// How PSCI functions are called.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PsciMethod {
+    Smc,
+    Hvc,
+}
+
+/// Calling convention of PSCI 0.2 and later, which decides the function IDs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PsciConvention {
+    /// SMC32 IDs, used by AArch32 callers and available to AArch64 ones.
+    Smc32,
+    /// SMC64 IDs, used by AArch64 callers for calls taking 64-bit arguments.
+    Smc64,
+}
+
+/// Function IDs of PSCI calls.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PsciFunctions {
+    pub cpu_suspend: Option<u32>,
+    pub cpu_off: Option<u32>,
+    pub cpu_on: Option<u32>,
+    pub migrate: Option<u32>,
+}
+
+/// The PSCI firmware node.
+#[derive(Clone)]
+pub struct Psci<'de> {
+    node: Node<'de>,
+    method: PsciMethod,
+}
+
+/// The architected timer node, `arm,armv8-timer` or `arm,armv7-timer`.
+#[derive(Clone)]
+pub struct ArmTimer<'de> {
+    node: Node<'de>,
+}
+
+const GIC_COMPATIBLES: &[(&str, GicVersion)] = &[
+    ("arm,gic-v3", GicVersion::V3),
+    ("arm,gic-400", GicVersion::V2),
+    ("arm,cortex-a15-gic", GicVersion::V2),
+    ("arm,cortex-a9-gic", GicVersion::V2),
+    ("arm,cortex-a7-gic", GicVersion::V2),
+];
+
+const GIC_TABLE: [&str; GIC_COMPATIBLES.len()] = {
+    let mut table = [""; GIC_COMPATIBLES.len()];
+    let mut i = 0;
+    while i < GIC_COMPATIBLES.len() {
+        table[i] = GIC_COMPATIBLES[i].0;
+        i += 1;
+    }
+    table
+};
+
+const PSCI_TABLE: &[&str] = &["arm,psci-1.0", "arm,psci-0.2", "arm,psci"];
+
+const TIMER_TABLE: &[&str] = &["arm,armv8-timer", "arm,armv7-timer"];
+
+impl PsciFunctions {
+    /// Standard SMC32 function IDs of PSCI 0.2 and later.
+    pub const SMC32: Self = Self {
+        cpu_suspend: Some(0x8400_0001),
+        cpu_off: Some(0x8400_0002),
+        cpu_on: Some(0x8400_0003),
+        migrate: Some(0x8400_0005),
+    };
+
+    /// Standard SMC64 function IDs of PSCI 0.2 and later.
+    ///
+    /// `CPU_OFF` takes no arguments and only has an SMC32 ID.
+    pub const SMC64: Self = Self {
+        cpu_suspend: Some(0xc400_0001),
+        cpu_off: Some(0x8400_0002),
+        cpu_on: Some(0xc400_0003),
+        migrate: Some(0xc400_0005),
+    };
+}
+
+impl<'de> Node<'de> {
+    /// Find the first enabled GIC in the tree.
+    pub fn gic(&self) -> Option<Gic<'de>> {
+        self.find_compatible(&GIC_TABLE)
+            .find(|(_, node)| node.is_enabled())
+            .map(|(index, node)| Gic {
+                node,
+                version: GIC_COMPATIBLES[index].1,
+            })
+    }
+
+    /// Find the PSCI firmware node.
+    pub fn psci(&self) -> Option<Psci<'de>> {
+        let (_, node) = self.find_compatible(PSCI_TABLE).next()?;
+        Psci::from_node(node)
+    }
+
+    /// Find the architected timer.
+    pub fn arm_timer(&self) -> Option<ArmTimer<'de>> {
+        let (_, node) = self.find_compatible(TIMER_TABLE).next()?;
+        Some(ArmTimer { node })
+    }
+
+    /// Decode the `interrupts` of this node as GIC specifiers.
+    ///
+    /// Returns `None` if the interrupt parent is not a GIC.
+    pub fn gic_interrupts(&self) -> Option<impl Iterator<Item = GicInterrupt> + 'de> {
+        let parent = self.interrupt_parent()?;
+        parent.compatible()?.matches(&GIC_TABLE)?;
+        // three cells, or four on GICv3 with PPI partitions
+        let cells = match parent.get_number("#interrupt-cells")? {
+            cells @ 3..=4 => cells as usize,
+            _ => return None,
+        };
+        let data = self.get_prop("interrupts")?.deserialize::<&[u8]>();
+        if !data.len().is_multiple_of(cells * 4) {
+            return None;
+        }
+        let specifiers = data.chunks_exact(cells * 4).filter_map(|specifier| {
+            // the optional fourth cell of GICv3, a PPI partition, is ignored
+            let cell =
+                |i: usize| u32::from_be_bytes(specifier[i * 4..i * 4 + 4].try_into().unwrap());
+            GicInterrupt::from_cells([cell(0), cell(1), cell(2)])
+        });
+        Some(specifiers)
+    }
+
+    /// The interrupt controller `interrupts` of this node are routed to,
+    /// from `interrupt-parent` of the node or its nearest ancestor.
+    fn interrupt_parent(&self) -> Option<Node<'de>> {
+        let mut node = self.clone();
+        loop {
+            if let Some(phandle) = node.get_number("interrupt-parent") {
+                return self.find_by_phandle(phandle as u32);
+            }
+            node = node.parent()?;
+        }
+    }
+}
+
+impl GicInterrupt {
+    /// Decode a specifier from its first three cells.
+    ///
+    /// Returns `None` if the type is unknown or the number is out of range for the type.
+    pub fn from_cells([kind, number, flags]: [u32; 3]) -> Option<Self> {
+        let (kind, count) = match kind {
+            0 => (GicIrqKind::Spi, 988),
+            1 => (GicIrqKind::Ppi, 16),
+            2 => (GicIrqKind::ExtendedSpi, 1024),
+            3 => (GicIrqKind::ExtendedPpi, 64),
+            _ => return None,
+        };
+        if number >= count {
+            return None;
+        }
+        Some(Self {
+            kind,
+            number,
+            flags,
+        })
+    }
+
+    /// The interrupt ID seen by the GIC.
+    pub fn intid(&self) -> u32 {
+        match self.kind {
+            GicIrqKind::Spi => self.number + 32,
+            GicIrqKind::Ppi => self.number + 16,
+            GicIrqKind::ExtendedSpi => self.number + 4096,
+            GicIrqKind::ExtendedPpi => self.number + 1056,
+        }
+    }
+
+    /// Trigger type in the low 4 bits of the flags:
+    /// 1 for rising edge, 2 for falling edge, 4 for high level and 8 for low level.
+    pub fn trigger(&self) -> u32 {
+        self.flags & 0xf
+    }
+
+    /// Processors a GICv2 PPI is wired to, bits 8 to 15 of the flags.
+    pub fn cpu_mask(&self) -> u8 {
+        (self.flags >> 8) as u8
+    }
+}
+
+impl<'de> Gic<'de> {
+    /// Recognize a GIC by its `compatible`.
+    pub fn from_node(node: Node<'de>) -> Option<Self> {
+        let index = node.compatible()?.matches(&GIC_TABLE)?;
+        Some(Self {
+            node,
+            version: GIC_COMPATIBLES[index].1,
+        })
+    }
+
+    /// The GIC node.
+    pub fn node(&self) -> &Node<'de> {
+        &self.node
+    }
+
+    /// Architecture version of the GIC.
+    pub fn version(&self) -> GicVersion {
+        self.version
+    }
+
+    /// The `reg` property, in the address space of the parent bus.
+    pub fn reg(&self) -> Option<Reg<'de>> {
+        Some(self.node.get_prop("reg")?.deserialize::<Reg>())
+    }
+
+    /// Distributor registers, GICD.
+    pub fn distributor(&self) -> Option<Range<u64>> {
+        self.region(0)
+    }
+
+    /// Redistributor regions of a GICv3, GICR.
+    ///
+    /// The number of regions is given by `#redistributor-regions`, defaults to 1.
+    /// The iterator stops at the first region missing from `reg` or failing to translate.
+    pub fn redistributors(&self) -> impl Iterator<Item = Range<u64>> + '_ {
+        (0..self.redistributor_count()).map_while(|i| self.region(1 + i))
+    }
+
+    /// Stride between redistributors of adjacent processors, from `redistributor-stride`.
+    pub fn redistributor_stride(&self) -> Option<u64> {
+        self.node.get_number("redistributor-stride")
+    }
+
+    /// CPU interface registers, GICC, which are optional on GICv3.
+    pub fn cpu_interface(&self) -> Option<Range<u64>> {
+        self.region(self.redistributor_count().checked_add(1)?)
+    }
+
+    /// Virtual interface control registers, GICH.
+    pub fn hypervisor_interface(&self) -> Option<Range<u64>> {
+        self.region(self.redistributor_count().checked_add(2)?)
+    }
+
+    /// Virtual CPU interface registers, GICV.
+    pub fn virtual_cpu_interface(&self) -> Option<Range<u64>> {
+        self.region(self.redistributor_count().checked_add(3)?)
+    }
+
+    /// The virtual GIC maintenance interrupt.
+    pub fn maintenance_interrupt(&self) -> Option<GicInterrupt> {
+        self.node.gic_interrupts()?.next()
+    }
+
+    /// Number of redistributor regions, at most the entries of `reg` after the distributor.
+    fn redistributor_count(&self) -> usize {
+        match self.version {
+            GicVersion::V2 => 0,
+            GicVersion::V3 => {
+                let count = self.node.get_number("#redistributor-regions").unwrap_or(1);
+                let entries = self.reg().map_or(0, |reg| reg.iter().count());
+                usize::try_from(count)
+                    .unwrap_or(usize::MAX)
+                    .min(entries.saturating_sub(1))
+            }
+        }
+    }
+
+    /// The `i`-th entry of `reg`, translated to a physical address range.
+    fn region(&self, i: usize) -> Option<Range<u64>> {
+        let region = self.reg()?.iter().nth(i)?.0;
+        let start = self.node.translate_address(region.start as u64)?;
+        Some(start..start.checked_add(region.len() as u64)?)
+    }
+}
+
+impl core::fmt::Debug for Gic<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Gic")
+            .field("version", &self.version)
+            .field("distributor", &self.distributor())
+            .finish()
+    }
+}
+
+impl<'de> Psci<'de> {
+    /// Read the PSCI node, returns `None` if `method` is missing or unknown.
+    pub fn from_node(node: Node<'de>) -> Option<Self> {
+        let method = match node
+            .get_prop("method")?
+            .deserialize::<StrSeq>()
+            .iter()
+            .next()?
+        {
+            "smc" => PsciMethod::Smc,
+            "hvc" => PsciMethod::Hvc,
+            _ => return None,
+        };
+        Some(Self { node, method })
+    }
+
+    /// The PSCI node.
+    pub fn node(&self) -> &Node<'de> {
+        &self.node
+    }
+
+    /// Conduit of PSCI calls, from `method`.
+    pub fn method(&self) -> PsciMethod {
+        self.method
+    }
+
+    /// Whether the firmware implements PSCI 0.2 or later.
+    pub fn is_v0_2_or_later(&self) -> bool {
+        self.node
+            .compatible()
+            .and_then(|compatible| compatible.matches(&PSCI_TABLE[..2]))
+            .is_some()
+    }
+
+    /// Function IDs of the PSCI calls.
+    ///
+    /// PSCI 0.2 and later use the standard IDs of `convention`: [`PsciFunctions::SMC32`]
+    /// for AArch32 callers, and usually [`PsciFunctions::SMC64`] for AArch64 ones.
+    /// The `cpu_suspend`, `cpu_off`, `cpu_on` and `migrate` properties only belong to
+    /// the plain `arm,psci` binding, so they are read, and `convention` ignored,
+    /// only when the node is not compatible with a later version.
+    pub fn functions(&self, convention: PsciConvention) -> PsciFunctions {
+        if self.is_v0_2_or_later() {
+            return match convention {
+                PsciConvention::Smc32 => PsciFunctions::SMC32,
+                PsciConvention::Smc64 => PsciFunctions::SMC64,
+            };
+        }
+        let id = |name| {
+            self.node
+                .get_number(name)
+                .and_then(|value| u32::try_from(value).ok())
+        };
+        PsciFunctions {
+            cpu_suspend: id("cpu_suspend"),
+            cpu_off: id("cpu_off"),
+            cpu_on: id("cpu_on"),
+            migrate: id("migrate"),
+        }
+    }
+}
+
+impl<'de> ArmTimer<'de> {
+    /// The timer node.
+    pub fn node(&self) -> &Node<'de> {
+        &self.node
+    }
+
+    /// Secure physical timer PPI.
+    pub fn secure_phys(&self) -> Option<GicInterrupt> {
+        self.interrupt(0)
+    }
+
+    /// Non-secure physical timer PPI.
+    pub fn phys(&self) -> Option<GicInterrupt> {
+        self.interrupt(1)
+    }
+
+    /// Virtual timer PPI.
+    pub fn virt(&self) -> Option<GicInterrupt> {
+        self.interrupt(2)
+    }
+
+    /// Hypervisor physical timer PPI.
+    pub fn hyp_phys(&self) -> Option<GicInterrupt> {
+        self.interrupt(3)
+    }
+
+    /// Hypervisor virtual timer PPI, only present on processors with the VHE extension.
+    pub fn hyp_virt(&self) -> Option<GicInterrupt> {
+        self.interrupt(4)
+    }
+
+    /// Frequency of the counter in Hz, from `clock-frequency`.
+    ///
+    /// Firmware should program `CNTFRQ_EL0` instead; this is only present when it does not.
+    pub fn clock_frequency(&self) -> Option<u32> {
+        self.node
+            .get_number("clock-frequency")
+            .and_then(|value| u32::try_from(value).ok())
+    }
+
+    /// Whether the timer keeps running in low power states, from `always-on`.
+    pub fn always_on(&self) -> bool {
+        self.node.get_prop("always-on").is_some()
+    }
+
+    fn interrupt(&self, i: usize) -> Option<GicInterrupt> {
+        self.node.gic_interrupts()?.nth(i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Gic, GicInterrupt, GicIrqKind, GicVersion, Psci, PsciConvention, PsciFunctions, PsciMethod,
+    };
+    use crate::buildin::Node;
+    use crate::{from_raw_mut, Dtb, DtbPtr};
+
+    const RAW_DEVICE_TREE: &[u8] = include_bytes!("../../examples/arm64.dtb");
+    const BUFFER_SIZE: usize = RAW_DEVICE_TREE.len();
+    #[test]
+    fn test_gic() {
+        #[repr(align(8))]
+        struct AlignedBuffer {
+            pub data: [u8; RAW_DEVICE_TREE.len()],
+        }
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE],
+        });
+        aligned_data.data[..BUFFER_SIZE].clone_from_slice(RAW_DEVICE_TREE);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        let gic = node.gic().unwrap();
+        assert_eq!(gic.version(), GicVersion::V3);
+        assert_eq!(gic.reg().unwrap().iter().count(), 6);
+        assert_eq!(gic.distributor(), Some(0x800_0000..0x801_0000));
+        let redistributors = gic.redistributors().collect::<Vec<_>>();
+        assert_eq!(
+            redistributors,
+            [0x80a_0000..0x900_0000, 0x900_0000..0x904_0000]
+        );
+        assert_eq!(gic.redistributor_stride(), Some(0x40000));
+        assert_eq!(gic.cpu_interface(), Some(0x801_0000..0x801_2000));
+        assert_eq!(gic.hypervisor_interface(), Some(0x803_0000..0x804_0000));
+        assert_eq!(gic.virtual_cpu_interface(), Some(0x804_0000..0x805_0000));
+        let maintenance = gic.maintenance_interrupt().unwrap();
+        assert_eq!(maintenance.intid(), 25);
+        assert_eq!(maintenance.trigger(), 4);
+
+        // the disabled GICv2 behind a bus with `ranges`
+        let gic = node.find("/soc/interrupt-controller@1000").unwrap();
+        let gic = Gic::from_node(gic).unwrap();
+        assert_eq!(gic.version(), GicVersion::V2);
+        assert_eq!(gic.redistributors().count(), 0);
+        assert_eq!(gic.distributor(), Some(0x2f00_1000..0x2f00_2000));
+        assert_eq!(gic.cpu_interface(), Some(0x2f00_2000..0x2f00_4000));
+        assert_eq!(gic.virtual_cpu_interface(), Some(0x2f00_6000..0x2f00_8000));
+        assert_eq!(gic.maintenance_interrupt().unwrap().cpu_mask(), 0xf);
+
+        // `#redistributor-regions` is capped by `reg`, and `#interrupt-cells` is rejected
+        let gic = node.find("/interrupt-controller@7000000").unwrap();
+        let gic = Gic::from_node(gic).unwrap();
+        let mut redistributors = gic.redistributors();
+        assert_eq!(redistributors.next(), Some(0x710_0000..0x720_0000));
+        assert_eq!(redistributors.next(), None);
+        assert_eq!(gic.cpu_interface(), None);
+        assert_eq!(gic.virtual_cpu_interface(), None);
+        assert!(gic.node().gic_interrupts().is_none());
+        assert_eq!(gic.maintenance_interrupt(), None);
+    }
+    #[test]
+    fn test_psci_timer() {
+        #[repr(align(8))]
+        struct AlignedBuffer {
+            pub data: [u8; RAW_DEVICE_TREE.len()],
+        }
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE],
+        });
+        aligned_data.data[..BUFFER_SIZE].clone_from_slice(RAW_DEVICE_TREE);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        let psci = node.psci().unwrap();
+        assert_eq!(psci.method(), PsciMethod::Smc);
+        assert!(psci.is_v0_2_or_later());
+        // the legacy IDs in the node are ignored
+        let functions = psci.functions(PsciConvention::Smc64);
+        assert_eq!(functions, PsciFunctions::SMC64);
+        assert_eq!(functions.cpu_on, Some(0xc400_0003));
+        let functions = psci.functions(PsciConvention::Smc32);
+        assert_eq!(functions, PsciFunctions::SMC32);
+        assert_eq!(functions.cpu_on, Some(0x8400_0003));
+
+        // PSCI 0.1 only has the IDs given by the node
+        let psci = Psci::from_node(node.find("/firmware/psci").unwrap()).unwrap();
+        assert_eq!(psci.method(), PsciMethod::Hvc);
+        assert!(!psci.is_v0_2_or_later());
+        let functions = psci.functions(PsciConvention::Smc64);
+        assert_eq!(functions, psci.functions(PsciConvention::Smc32));
+        assert_eq!(functions.cpu_on, Some(0x95c1_0002));
+        assert_eq!(functions.cpu_off, Some(0x95c1_0001));
+        assert_eq!(functions.cpu_suspend, None);
+        assert_eq!(functions.migrate, None);
+
+        let timer = node.arm_timer().unwrap();
+        assert_eq!(timer.clock_frequency(), Some(25_000_000));
+        assert!(timer.always_on());
+        assert_eq!(
+            timer.phys(),
+            Some(GicInterrupt {
+                kind: GicIrqKind::Ppi,
+                number: 14,
+                flags: 0xf08,
+            })
+        );
+        assert_eq!(timer.virt().unwrap().intid(), 27);
+        assert_eq!(timer.secure_phys().unwrap().intid(), 29);
+        assert_eq!(timer.hyp_phys().unwrap().intid(), 26);
+        assert_eq!(timer.hyp_virt(), None);
+        assert_eq!(GicInterrupt::from_cells([4, 0, 0]), None);
+        // numbers beyond the range of each type
+        assert_eq!(GicInterrupt::from_cells([0, 0xffff_ffff, 4]), None);
+        assert_eq!(GicInterrupt::from_cells([0, 988, 4]), None);
+        assert_eq!(GicInterrupt::from_cells([1, 16, 4]), None);
+        assert_eq!(GicInterrupt::from_cells([2, 1024, 4]), None);
+        assert_eq!(GicInterrupt::from_cells([3, 64, 4]), None);
+        assert_eq!(
+            GicInterrupt::from_cells([2, 1023, 4]).unwrap().intid(),
+            5119
+        );
+        assert_eq!(GicInterrupt::from_cells([3, 63, 4]).unwrap().intid(), 1119);
+    }
+}
@@ -1,4 +1,117 @@
-use crate::buildin::{Node, StrSeq};
+use crate::buildin::{CellArray, Node, Reg, StrSeq};
+use core::ops::Range;
+use serde::Deserialize;
+
+/// Boot information passed in the `/chosen` node.
+///
+/// Every property is optional; a property that is missing or malformed reads as `None`.
+pub struct Chosen<'de> {
+    bootargs: Option<&'de str>,
+    stdin_path: Option<&'de str>,
+    stdout_path: Option<&'de str>,
+    initrd: Option<Range<u64>>,
+    rng_seed: Option<&'de [u8]>,
+    kaslr_seed: Option<u64>,
+    elfcorehdr: Option<Range<u64>>,
+    usable_memory_range: Option<Reg<'de>>,
+    booted_from_kexec: bool,
+}
+
+impl<'de> Chosen<'de> {
+    /// Read all boot information from a `/chosen` node.
+    pub fn from_node(node: &Node<'de>) -> Self {
+        let initrd = match (
+            node.get_number("linux,initrd-start"),
+            node.get_number("linux,initrd-end"),
+        ) {
+            (Some(start), Some(end)) if start <= end => Some(start..end),
+            _ => None,
+        };
+        // decoded as 64 bits, `Reg` would truncate the region on 32-bit targets
+        let elfcorehdr = node.get_prop("linux,elfcorehdr").and_then(|prop| {
+            let parent = node.parent()?;
+            let widths = [
+                region_cells(&parent, "#address-cells", 2)?,
+                region_cells(&parent, "#size-cells", 1)?,
+            ];
+            let data = CellArray::new(prop.deserialize::<&[u8]>());
+            let [start, size] = data.entries(widths).ok()?.next()?;
+            Some(start..start.checked_add(size)?)
+        });
+        Self {
+            bootargs: first_str(node, "bootargs"),
+            stdin_path: first_str(node, "stdin-path").map(strip_options),
+            stdout_path: first_str(node, "stdout-path").map(strip_options),
+            initrd,
+            rng_seed: node
+                .get_prop("rng-seed")
+                .map(|prop| prop.deserialize::<&[u8]>()),
+            kaslr_seed: node.get_number("kaslr-seed"),
+            elfcorehdr,
+            usable_memory_range: node
+                .get_prop("linux,usable-memory-range")
+                .map(|prop| prop.deserialize::<Reg>()),
+            booted_from_kexec: node.get_prop("linux,booted-from-kexec").is_some(),
+        }
+    }
+
+    /// Kernel command line, from `bootargs`.
+    pub fn bootargs(&self) -> Option<&'de str> {
+        self.bootargs
+    }
+
+    /// Path or alias of the console input device, without options.
+    pub fn stdin_path(&self) -> Option<&'de str> {
+        self.stdin_path
+    }
+
+    /// Path or alias of the console output device, without options.
+    pub fn stdout_path(&self) -> Option<&'de str> {
+        self.stdout_path
+    }
+
+    /// Physical address range of the initial ramdisk,
+    /// from `linux,initrd-start` and `linux,initrd-end`, each of which may be 32 or 64 bits.
+    pub fn initrd(&self) -> Option<Range<u64>> {
+        self.initrd.clone()
+    }
+
+    /// Entropy for seeding the kernel random number generator, from `rng-seed`.
+    pub fn rng_seed(&self) -> Option<&'de [u8]> {
+        self.rng_seed
+    }
+
+    /// Seed for kernel address space layout randomization, from `kaslr-seed`.
+    pub fn kaslr_seed(&self) -> Option<u64> {
+        self.kaslr_seed
+    }
+
+    /// Physical address range of the ELF core header of a crashed kernel,
+    /// from `linux,elfcorehdr`, or `None` if the range ends beyond the address space.
+    pub fn elfcorehdr(&self) -> Option<Range<u64>> {
+        self.elfcorehdr.clone()
+    }
+
+    /// Memory the crash dump kernel may use, from `linux,usable-memory-range`.
+    pub fn usable_memory_range(&self) -> Option<&Reg<'de>> {
+        self.usable_memory_range.as_ref()
+    }
+
+    /// Whether this kernel was started by kexec, from `linux,booted-from-kexec`.
+    pub fn booted_from_kexec(&self) -> bool {
+        self.booted_from_kexec
+    }
+}
+
+impl<'de> Deserialize<'de> for Chosen<'_> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let node = <Node as Deserialize>::deserialize(deserializer)?;
+        Ok(Chosen::from_node(&node))
+    }
+}
 
 impl<'de> Node<'de> {
     /// Get node /chosen
@@ -8,37 +121,38 @@ impl<'de> Node<'de> {
     }
     /// Get /chosen/stdin-path
     pub fn chosen_stdin_path(&self) -> Option<&'de str> {
-        let result = self
-            .chosen()?
-            .get_prop("stdin-path")?
-            .deserialize::<StrSeq>()
-            .iter()
-            .next()?;
-        if let Some(pos) = result.find(':') {
-            Some(result.split_at(pos).0)
-        } else {
-            Some(result)
-        }
+        first_str(&self.chosen()?, "stdin-path").map(strip_options)
     }
     /// Get /chosen/stdout-path
     pub fn chosen_stdout_path(&self) -> Option<&'de str> {
-        let result = self
-            .chosen()?
-            .get_prop("stdout-path")?
-            .deserialize::<StrSeq>()
-            .iter()
-            .next()?;
-        if let Some(pos) = result.find(':') {
-            Some(result.split_at(pos).0)
-        } else {
-            Some(result)
-        }
+        first_str(&self.chosen()?, "stdout-path").map(strip_options)
+    }
+}
+
+/// The first string of a string list property.
+fn first_str<'de>(node: &Node<'de>, name: &str) -> Option<&'de str> {
+    node.get_prop(name)?.deserialize::<StrSeq>().iter().next()
+}
+
+/// `#address-cells` or `#size-cells` of `node`, `None` if it does not fit in 64 bits.
+fn region_cells(node: &Node, name: &str, default: u64) -> Option<usize> {
+    let cells = node.get_number(name).unwrap_or(default);
+    (cells <= 2).then_some(cells as usize)
+}
+
+/// Remove the `:options` suffix of a console path.
+fn strip_options(path: &str) -> &str {
+    match path.find(':') {
+        Some(pos) => path.split_at(pos).0,
+        None => path,
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::Chosen;
     use crate::{buildin::Node, from_raw_mut, Dtb, DtbPtr};
+    use serde_derive::Deserialize;
 
     const RAW_DEVICE_TREE: &[u8] = include_bytes!("../../examples/bl808.dtb");
     const BUFFER_SIZE: usize = RAW_DEVICE_TREE.len();
@@ -60,4 +174,77 @@ mod tests {
         assert!(node.chosen().is_some());
         assert_eq!(node.chosen_stdout_path(), Some("serial3"));
     }
+    const RAW_DEVICE_TREE_CHOSEN: &[u8] = include_bytes!("../../examples/chosen.dtb");
+    const BUFFER_SIZE_CHOSEN: usize = RAW_DEVICE_TREE_CHOSEN.len();
+    const RAW_DEVICE_TREE_QEMU: &[u8] = include_bytes!("../../examples/qemu-virt.dtb");
+    const BUFFER_SIZE_QEMU: usize = RAW_DEVICE_TREE_QEMU.len();
+    #[derive(Deserialize)]
+    struct Tree<'a> {
+        chosen: Chosen<'a>,
+    }
+    #[test]
+    fn test_chosen() {
+        #[repr(align(8))]
+        struct AlignedBuffer {
+            pub data: [u8; RAW_DEVICE_TREE_CHOSEN.len()],
+        }
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE_CHOSEN],
+        });
+        aligned_data.data[..BUFFER_SIZE_CHOSEN].clone_from_slice(RAW_DEVICE_TREE_CHOSEN);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let t: Tree = from_raw_mut(&dtb).unwrap();
+        let chosen = t.chosen;
+        assert_eq!(
+            chosen.bootargs(),
+            Some("console=ttyS0 earlycon root=/dev/vda rw")
+        );
+        assert_eq!(chosen.stdout_path(), Some("serial0"));
+        assert_eq!(chosen.stdin_path(), None);
+        // 32-bit start, 64-bit end
+        assert_eq!(chosen.initrd(), Some(0x88000000..0x88200000));
+        assert_eq!(chosen.rng_seed(), Some(&[1, 2, 3, 4, 5, 6, 7, 8][..]));
+        assert_eq!(chosen.kaslr_seed(), Some(0x12345678_9abcdef0));
+        assert_eq!(chosen.elfcorehdr(), Some(0x1_bff00000..0x1_bff10000));
+        let usable = chosen
+            .usable_memory_range()
+            .unwrap()
+            .iter()
+            .map(|region| region.0)
+            .collect::<Vec<_>>();
+        assert_eq!(usable, [0xa0000000..0xb0000000, 0x100000000..0x108000000]);
+        assert!(chosen.booted_from_kexec());
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        let chosen = Chosen::from_node(&node.chosen().unwrap());
+        assert_eq!(chosen.initrd(), Some(0x88000000..0x88200000));
+        let overflow = Chosen::from_node(&node.find("/kexec-overflow").unwrap());
+        assert_eq!(overflow.elfcorehdr(), None);
+    }
+    #[test]
+    fn test_chosen_rng_seed() {
+        #[repr(align(8))]
+        struct AlignedBuffer {
+            pub data: [u8; RAW_DEVICE_TREE_QEMU.len()],
+        }
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE_QEMU],
+        });
+        aligned_data.data[..BUFFER_SIZE_QEMU].clone_from_slice(RAW_DEVICE_TREE_QEMU);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        let chosen = Chosen::from_node(&node.chosen().unwrap());
+        assert_eq!(chosen.stdout_path(), Some("/soc/serial@10000000"));
+        assert_eq!(chosen.rng_seed().map(<[u8]>::len), Some(32));
+        assert_eq!(chosen.bootargs(), None);
+        assert_eq!(chosen.initrd(), None);
+        assert!(chosen.usable_memory_range().is_none());
+        assert!(!chosen.booted_from_kexec());
+    }
 }
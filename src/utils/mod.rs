@@ -1,68 +1,145 @@
+pub mod arm;
 pub mod chosen;
+pub mod riscv;
+pub mod riscv_irq;
+pub mod serial;
+pub mod symbols;
 
 use crate::buildin::{Node, StrSeq};
+use crate::Compatible;
 
 impl<'de> Node<'de> {
     /// Try to get a node by a full-path.
     fn raw_find<'b>(&'b self, path: &str) -> Option<Node<'de>> {
-        // Direct return root node
-        let mut current_node = Some(self.clone());
-        if path == "/" {
-            return current_node;
+        self.find_relative(path.strip_prefix('/')?)
+    }
+
+    /// Follow `/` separated node names down from this node.
+    fn find_relative(&self, path: &str) -> Option<Node<'de>> {
+        let mut current_node = self.clone();
+        for current_name in path.split('/').filter(|name| !name.is_empty()) {
+            current_node = current_node.find_child(current_name)?;
+        }
+        Some(current_node)
+    }
+
+    /// Find a direct child by name.
+    ///
+    /// A name without unit address matches a child with any unit address,
+    /// as long as only one child has that node name; an exact full name match always wins.
+    fn find_child(&self, name: &str) -> Option<Node<'de>> {
+        if let Some(child) = self.nodes().find(|x| x.get_full_name() == name) {
+            return Some(child.to_node());
         }
-        let (root, path) = path.split_at(1);
-        if root != "/" {
+        if name.contains('@') {
             return None;
         }
-        // Split path with / and find each level
-        for current_name in path.split('/') {
-            let node = match current_node.clone() {
-                Some(node) => node,
-                None => break,
-            };
-            let next_node_iter = node.nodes().find(|x| x.get_full_name() == current_name);
-            match next_node_iter {
-                None => current_node = None,
-                Some(iter) => {
-                    let next_node = iter.deserialize::<Node>();
-                    current_node = Some(next_node);
-                }
-            }
+        let mut candidates = self
+            .nodes()
+            .filter(|x| x.get_full_name().split('@').next() == Some(name));
+        match (candidates.next(), candidates.next()) {
+            (Some(child), None) => Some(child.to_node()),
+            _ => None,
         }
-        current_node
     }
+
+    /// Get the full path an alias refers to.
+    fn resolve_alias(&self, alias: &str) -> Option<&'de str> {
+        // As spec 3.3 said, this prop value should be one string,
+        // which is a full path ref to a node.
+        self.raw_find("/aliases")?
+            .get_prop(alias)?
+            .deserialize::<StrSeq>()
+            .iter()
+            .next()
+    }
+
     /// Try to get a node by path.
+    ///
+    /// The path is either a full path, or starts with an alias as its first component,
+    /// such as `serial0` or `ethernet0/ethernet-phy@0`.
+    /// Node names may omit the unit address when unambiguous,
+    /// and anything after `:` (e.g. options in `stdout-path`) is ignored.
     pub fn find<'b>(&'b self, path: &str) -> Option<Node<'de>> {
-        // Direct return root node
-        let current_node = Some(self.clone());
-        if path == "/" {
-            return current_node;
+        let path = path.split_once(':').map_or(path, |(path, _)| path);
+        if path.starts_with('/') {
+            return self.raw_find(path);
         }
-        let (root, _) = path.split_at(1);
-        if root != "/" {
-            // Path name does not start with `/`, Check if the aliases.
-            if let Some(aliases) = self.raw_find("/aliases") {
-                if let Some(full_path) = aliases.get_prop(path) {
-                    // As spec 3.3 said, this prop value should be one string,
-                    // which is a full path ref to a node.
-                    let full_path = full_path.deserialize::<StrSeq>();
-                    return self.raw_find(full_path.iter().next().unwrap());
-                }
-            }
+        // Path name does not start with `/`, Check if the aliases.
+        let (alias, rest) = path.split_once('/').unwrap_or((path, ""));
+        if alias.is_empty() {
             return None;
         }
-        self.raw_find(path)
+        self.raw_find(self.resolve_alias(alias)?)?
+            .find_relative(rest)
+    }
+
+    /// Get the phandle of this node, from `phandle` or the legacy `linux,phandle`.
+    pub fn phandle(&self) -> Option<u32> {
+        self.props()
+            .find(|prop| matches!(prop.get_name(), "phandle" | "linux,phandle"))
+            .map(|prop| prop.deserialize::<u32>())
+    }
+
+    /// Try to get the node referenced by `phandle`, searching the whole tree.
+    pub fn find_by_phandle(&self, phandle: u32) -> Option<Node<'de>> {
+        if let Some(found) = self.find_by_phandle_indexed(phandle) {
+            return found;
+        }
+        self.root()
+            .descendants()
+            .map(|(_, node)| node)
+            .find(|node| node.phandle() == Some(phandle))
+    }
+
+    /// Get the `compatible` property of this node.
+    pub fn compatible(&self) -> Option<Compatible<'de>> {
+        self.get_prop("compatible")
+            .map(|prop| prop.deserialize::<Compatible>())
+    }
+
+    /// Lazily find every node in the whole tree whose `compatible` matches the driver table.
+    ///
+    /// Each item is the index into `table` of the best match (see [`Compatible::matches`])
+    /// and the matched node, in depth-first order.
+    pub fn find_compatible<'b>(
+        &self,
+        table: &'b [&'b str],
+    ) -> impl Iterator<Item = (usize, Node<'de>)> + 'b
+    where
+        'de: 'b,
+    {
+        self.root().descendants().filter_map(|(_, node)| {
+            let index = node.compatible()?.matches(table)?;
+            Some((index, node))
+        })
     }
 
     /// use depth-first search to traversal the tree, and exec func for each node
+    ///
+    /// This is a thin wrapper over [`Node::descendants`], which can also stop early
+    /// and skip subtrees.
     pub fn search<F>(&self, func: &mut F)
     where
         F: FnMut(&Node),
     {
-        func(self);
-        for node in self.nodes() {
-            let node = node.deserialize::<Node>();
-            node.search(func);
+        for (_, node) in self.descendants() {
+            func(&node);
+        }
+    }
+
+    /// Like [`Node::search`], but skip disabled nodes together with their subtrees.
+    pub fn search_enabled<F>(&self, func: &mut F)
+    where
+        F: FnMut(&Node),
+    {
+        let mut iter = self.descendants();
+        while let Some((_, node)) = iter.next() {
+            if node.is_enabled() {
+                func(&node);
+            } else {
+                iter.skip_subtree();
+            }
         }
     }
 }
@@ -98,6 +175,39 @@ mod tests {
         let mut closure = |_node: &Node| count += 1;
         node.search(&mut closure);
         assert_eq!(count, 70);
+        assert_eq!(node.descendants().count(), 70);
+        let mut count = 0;
+        let mut closure = |_node: &Node| count += 1;
+        node.search_enabled(&mut closure);
+        assert_eq!(count, 68);
+    }
+    #[test]
+    fn test_find_compatible() {
+        #[repr(align(8))]
+        struct AlignedBuffer {
+            pub data: [u8; RAW_DEVICE_TREE.len()],
+        }
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE],
+        });
+        aligned_data.data[..BUFFER_SIZE].clone_from_slice(RAW_DEVICE_TREE);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        const DRIVERS: &[&str] = &["sifive,spi0", "sifive,uart0", "sifive,fu740-c000-uart"];
+        let matched = node.find_compatible(DRIVERS).collect::<Vec<_>>();
+        assert_eq!(matched.len(), 5);
+        // `sifive,fu740-c000-uart` is more specific than `sifive,uart0`
+        assert_eq!(matched[0].0, 2);
+        assert_eq!(matched[1].0, 2);
+        assert_eq!(matched[2].0, 0);
+        assert_eq!(matched[0].1.phandle(), None);
+        let cpu = node.find("/cpus/cpu@1").unwrap();
+        assert_eq!(cpu.compatible().unwrap().matches(&["riscv"]), Some(0));
+        assert_eq!(cpu.compatible().unwrap().matches(&["arm,cortex-a53"]), None);
+        assert_eq!(node.find_compatible(&["riscv,cpu-intc"]).count(), 5);
     }
     #[test]
     fn test_find() {
@@ -131,4 +241,39 @@ mod tests {
             None => panic!("failed to find /chosen/stdout-path"),
         }
     }
+    #[test]
+    fn test_find_spec() {
+        #[repr(align(8))]
+        struct AlignedBuffer {
+            pub data: [u8; RAW_DEVICE_TREE.len()],
+        }
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE],
+        });
+        aligned_data.data[..BUFFER_SIZE].clone_from_slice(RAW_DEVICE_TREE);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        let phy = node.find("/soc/ethernet/ethernet-phy").unwrap();
+        assert_eq!(
+            phy.path().to_string(),
+            "/soc/ethernet@10090000/ethernet-phy@0"
+        );
+        // Two serial ports, so the name alone is ambiguous
+        assert!(node.find("/soc/serial").is_none());
+        assert!(node.find("/soc/serial@10011000").is_some());
+        assert!(node.find("/soc/serial@1001").is_none());
+        // Aliases, with options and relative paths
+        let serial0 = node.find("serial0:115200n8").unwrap();
+        assert_eq!(serial0.path().to_string(), "/soc/serial@10010000");
+        let phy = node.find("ethernet0/ethernet-phy@0").unwrap();
+        assert_eq!(phy.name(), "ethernet-phy@0");
+        assert!(node.find("ethernet0/nonexistent").is_none());
+        assert!(node.find("nonexistent").is_none());
+        assert!(node.find("").is_none());
+        assert!(node.find(":").is_none());
+        assert!(node.find("/").is_some());
+    }
 }
@@ -0,0 +1,559 @@
+use crate::buildin::StrSeq;
+use crate::{Cpu, Cpus};
+use core::fmt;
+
+/// A RISC-V ISA extension known to this crate.
+///
+/// Extensions not listed here are ignored when parsing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum Extension {
+    I,
+    E,
+    M,
+    A,
+    F,
+    D,
+    Q,
+    C,
+    B,
+    V,
+    H,
+    Zicbom,
+    Zicbop,
+    Zicboz,
+    Zicntr,
+    Zicond,
+    Zicsr,
+    Zifencei,
+    Zihintntl,
+    Zihintpause,
+    Zihpm,
+    Zimop,
+    Zmmul,
+    Zaamo,
+    Zacas,
+    Zalrsc,
+    Zawrs,
+    Zfa,
+    Zfh,
+    Zfhmin,
+    Zca,
+    Zcb,
+    Zcd,
+    Zcf,
+    Zcmop,
+    Zba,
+    Zbb,
+    Zbc,
+    Zbkb,
+    Zbkc,
+    Zbkx,
+    Zbs,
+    Zkn,
+    Zkr,
+    Zks,
+    Zkt,
+    Smaia,
+    Smstateen,
+    Ssaia,
+    Sscofpmf,
+    Ssstateen,
+    Sstc,
+    Svadu,
+    Svinval,
+    Svnapot,
+    Svpbmt,
+}
+
+impl Extension {
+    /// Every known extension, in the order of their bits in [`IsaExtensions`].
+    pub const ALL: [Self; 56] = {
+        use Extension::*;
+        [
+            I,
+            E,
+            M,
+            A,
+            F,
+            D,
+            Q,
+            C,
+            B,
+            V,
+            H,
+            Zicbom,
+            Zicbop,
+            Zicboz,
+            Zicntr,
+            Zicond,
+            Zicsr,
+            Zifencei,
+            Zihintntl,
+            Zihintpause,
+            Zihpm,
+            Zimop,
+            Zmmul,
+            Zaamo,
+            Zacas,
+            Zalrsc,
+            Zawrs,
+            Zfa,
+            Zfh,
+            Zfhmin,
+            Zca,
+            Zcb,
+            Zcd,
+            Zcf,
+            Zcmop,
+            Zba,
+            Zbb,
+            Zbc,
+            Zbkb,
+            Zbkc,
+            Zbkx,
+            Zbs,
+            Zkn,
+            Zkr,
+            Zks,
+            Zkt,
+            Smaia,
+            Smstateen,
+            Ssaia,
+            Sscofpmf,
+            Ssstateen,
+            Sstc,
+            Svadu,
+            Svinval,
+            Svnapot,
+            Svpbmt,
+        ]
+    };
+
+    const NAMES: [&'static str; 56] = [
+        "i",
+        "e",
+        "m",
+        "a",
+        "f",
+        "d",
+        "q",
+        "c",
+        "b",
+        "v",
+        "h",
+        "zicbom",
+        "zicbop",
+        "zicboz",
+        "zicntr",
+        "zicond",
+        "zicsr",
+        "zifencei",
+        "zihintntl",
+        "zihintpause",
+        "zihpm",
+        "zimop",
+        "zmmul",
+        "zaamo",
+        "zacas",
+        "zalrsc",
+        "zawrs",
+        "zfa",
+        "zfh",
+        "zfhmin",
+        "zca",
+        "zcb",
+        "zcd",
+        "zcf",
+        "zcmop",
+        "zba",
+        "zbb",
+        "zbc",
+        "zbkb",
+        "zbkc",
+        "zbkx",
+        "zbs",
+        "zkn",
+        "zkr",
+        "zks",
+        "zkt",
+        "smaia",
+        "smstateen",
+        "ssaia",
+        "sscofpmf",
+        "ssstateen",
+        "sstc",
+        "svadu",
+        "svinval",
+        "svnapot",
+        "svpbmt",
+    ];
+
+    /// Lower case name of the extension as written in an ISA string.
+    pub const fn name(self) -> &'static str {
+        Self::NAMES[self as usize]
+    }
+
+    /// Look up an extension by its name, ignoring case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::NAMES
+            .iter()
+            .position(|known| known.eq_ignore_ascii_case(name))
+            .map(|i| Self::ALL[i])
+    }
+}
+
+/// A set of RISC-V ISA extensions.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct IsaExtensions(u64);
+
+impl IsaExtensions {
+    /// The empty set.
+    pub const EMPTY: Self = Self(0);
+
+    /// Whether `ext` is in the set.
+    pub const fn contains(&self, ext: Extension) -> bool {
+        self.0 & (1 << ext as u8) != 0
+    }
+
+    /// Add `ext` to the set.
+    pub fn insert(&mut self, ext: Extension) {
+        self.0 |= 1 << ext as u8;
+    }
+
+    /// Extensions present in both sets.
+    pub const fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    /// Extensions present in either set.
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    /// Whether every extension of `other` is also in this set.
+    pub const fn is_superset(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Whether the set is empty.
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Raw bits, bit `n` stands for `Extension::ALL[n]`.
+    pub const fn bits(&self) -> u64 {
+        self.0
+    }
+
+    /// Iterate over the extensions in the set.
+    pub fn iter(&self) -> impl Iterator<Item = Extension> + '_ {
+        Extension::ALL
+            .iter()
+            .copied()
+            .filter(|ext| self.contains(*ext))
+    }
+
+    /// Add an extension by name, together with the extensions it implies.
+    ///
+    /// Unknown extensions are ignored.
+    fn insert_name(&mut self, name: &str) {
+        use Extension::*;
+        if name.eq_ignore_ascii_case("g") {
+            for ext in [I, M, A, F, D, Zicsr, Zifencei] {
+                self.insert(ext);
+            }
+            return;
+        }
+        let Some(ext) = Extension::from_name(name) else {
+            return;
+        };
+        self.insert(ext);
+        match ext {
+            B => [Zba, Zbb, Zbs].into_iter().for_each(|ext| self.insert(ext)),
+            A => [Zaamo, Zalrsc].into_iter().for_each(|ext| self.insert(ext)),
+            M => self.insert(Zmmul),
+            _ => {}
+        }
+    }
+}
+
+impl FromIterator<Extension> for IsaExtensions {
+    fn from_iter<T: IntoIterator<Item = Extension>>(iter: T) -> Self {
+        let mut set = Self::EMPTY;
+        iter.into_iter().for_each(|ext| set.insert(ext));
+        set
+    }
+}
+
+impl fmt::Debug for IsaExtensions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set()
+            .entries(self.iter().map(Extension::name))
+            .finish()
+    }
+}
+
+/// ISA of one hart, from the `riscv,*` properties of its `cpu` node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HartIsa {
+    /// Base integer register width, 32 or 64.
+    pub xlen: u32,
+    pub extensions: IsaExtensions,
+    /// Size in bytes of the block managed by Zicbom instructions.
+    pub cbom_block_size: Option<u32>,
+    /// Size in bytes of the block zeroed by Zicboz instructions.
+    pub cboz_block_size: Option<u32>,
+}
+
+impl HartIsa {
+    /// Parse an ISA string like `rv64imafdc_zicsr_zifencei_sstc`.
+    ///
+    /// Version numbers are skipped and unknown extensions are ignored.
+    /// Returns `None` if the string does not start with `rv32` or `rv64`.
+    pub fn parse_isa_str(isa: &str) -> Option<(u32, IsaExtensions)> {
+        let (xlen, mut rest) = parse_base(isa)?;
+        let mut extensions = IsaExtensions::EMPTY;
+        // single letter extensions, until the first multi-letter one
+        while let Some(c) = rest.chars().next() {
+            let lower = c.to_ascii_lowercase();
+            if c == '_' || lower == 'z' || lower == 'x' {
+                break;
+            }
+            if lower == 's' {
+                // legacy privilege mode letters `su` written by older firmware
+                if rest[1..].starts_with(['u', 'U']) {
+                    rest = &rest[2..];
+                    continue;
+                }
+                break;
+            }
+            rest = &rest[c.len_utf8()..];
+            if lower == 'u' {
+                continue;
+            }
+            if c.is_ascii_alphabetic() {
+                extensions.insert_name(c.encode_utf8(&mut [0; 4]));
+            }
+            rest = skip_version(rest);
+        }
+        for name in rest.split('_').filter(|name| !name.is_empty()) {
+            extensions.insert_name(strip_version(name));
+        }
+        Some((xlen, extensions))
+    }
+
+    /// Read the ISA of a hart from its `cpu` node.
+    ///
+    /// `riscv,isa-base` together with `riscv,isa-extensions` is preferred over `riscv,isa`.
+    pub fn from_cpu(cpu: &Cpu) -> Option<Self> {
+        let node = cpu.node();
+        let str_prop = |name| {
+            node.get_prop(name)
+                .and_then(|prop| prop.deserialize::<StrSeq>().iter().next())
+        };
+        let (xlen, extensions) = match (
+            str_prop("riscv,isa-base"),
+            node.get_prop("riscv,isa-extensions"),
+        ) {
+            (Some(base), Some(list)) => {
+                let (xlen, mut extensions) = Self::parse_isa_str(base)?;
+                for name in list.deserialize::<StrSeq>().iter() {
+                    extensions.insert_name(name);
+                }
+                (xlen, extensions)
+            }
+            _ => Self::parse_isa_str(str_prop("riscv,isa")?)?,
+        };
+        let u32_prop = |name| {
+            node.get_number(name)
+                .and_then(|value| u32::try_from(value).ok())
+        };
+        Some(Self {
+            xlen,
+            extensions,
+            cbom_block_size: u32_prop("riscv,cbom-block-size"),
+            cboz_block_size: u32_prop("riscv,cboz-block-size"),
+        })
+    }
+
+    /// The ISA supported by both harts.
+    ///
+    /// Returns `None` if the harts have different register widths.
+    /// A block size is kept only if both harts agree on it.
+    pub fn common(&self, other: &Self) -> Option<Self> {
+        let same = |a: Option<u32>, b: Option<u32>| if a == b { a } else { None };
+        (self.xlen == other.xlen).then(|| Self {
+            xlen: self.xlen,
+            extensions: self.extensions.intersection(other.extensions),
+            cbom_block_size: same(self.cbom_block_size, other.cbom_block_size),
+            cboz_block_size: same(self.cboz_block_size, other.cboz_block_size),
+        })
+    }
+}
+
+impl Cpu<'_> {
+    /// The RISC-V ISA of this hart.
+    pub fn riscv_isa(&self) -> Option<HartIsa> {
+        HartIsa::from_cpu(self)
+    }
+}
+
+impl Cpus<'_> {
+    /// The RISC-V ISA of the hart with ID `hart_id`.
+    pub fn riscv_isa(&self, hart_id: u64) -> Option<HartIsa> {
+        self.by_id(hart_id)?.riscv_isa()
+    }
+
+    /// The RISC-V ISA supported by every enabled hart.
+    ///
+    /// Returns `None` if there is no enabled hart, if one of them has no valid ISA,
+    /// or if their register widths differ.
+    pub fn riscv_common_isa(&self) -> Option<HartIsa> {
+        let mut harts = self.iter_enabled();
+        let first = harts.next()?.riscv_isa()?;
+        harts.try_fold(first, |common, cpu| common.common(&cpu.riscv_isa()?))
+    }
+}
+
+/// Split `rv32`/`rv64` from an ISA string.
+fn parse_base(isa: &str) -> Option<(u32, &str)> {
+    let prefix = isa.get(..4)?;
+    let xlen = if prefix.eq_ignore_ascii_case("rv32") {
+        32
+    } else if prefix.eq_ignore_ascii_case("rv64") {
+        64
+    } else {
+        return None;
+    };
+    Some((xlen, &isa[4..]))
+}
+
+/// Skip a version number like `2p1` after a single letter extension.
+fn skip_version(s: &str) -> &str {
+    let digits = |s: &str| s.bytes().take_while(u8::is_ascii_digit).count();
+    let major = digits(s);
+    if major == 0 {
+        return s;
+    }
+    let rest = &s[major..];
+    match rest.strip_prefix(['p', 'P']) {
+        Some(minor) if digits(minor) > 0 => &minor[digits(minor)..],
+        _ => rest,
+    }
+}
+
+/// Remove a version number like `2p0` from the end of a multi-letter extension.
+fn strip_version(name: &str) -> &str {
+    let trimmed = name.trim_end_matches(|c: char| c.is_ascii_digit());
+    if trimmed.len() == name.len() {
+        return name;
+    }
+    match trimmed.strip_suffix(['p', 'P']) {
+        Some(major) if major.ends_with(|c: char| c.is_ascii_digit()) => {
+            major.trim_end_matches(|c: char| c.is_ascii_digit())
+        }
+        _ => trimmed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Extension, HartIsa, IsaExtensions};
+    use crate::buildin::{Node, StrSeq};
+    use crate::{from_raw_mut, Dtb, DtbPtr};
+
+    const RAW_DEVICE_TREE_QEMU: &[u8] = include_bytes!("../../examples/qemu-virt.dtb");
+    const BUFFER_SIZE_QEMU: usize = RAW_DEVICE_TREE_QEMU.len();
+    const RAW_DEVICE_TREE_HIFIVE: &[u8] = include_bytes!("../../examples/hifive-unmatched-a00.dtb");
+    const BUFFER_SIZE_HIFIVE: usize = RAW_DEVICE_TREE_HIFIVE.len();
+    #[test]
+    fn test_parse_isa_str() {
+        use Extension::*;
+        let (xlen, ext) = HartIsa::parse_isa_str("rv64imafdc_zicsr_zifencei_sstc").unwrap();
+        assert_eq!(xlen, 64);
+        let expected = [
+            I, M, A, F, D, C, Zicsr, Zifencei, Sstc, Zmmul, Zaamo, Zalrsc,
+        ];
+        assert_eq!(ext, expected.into_iter().collect::<IsaExtensions>());
+
+        let (xlen, ext) = HartIsa::parse_isa_str("RV32GC").unwrap();
+        assert_eq!(xlen, 32);
+        assert!(ext.contains(Zifencei) && ext.contains(C) && !ext.contains(V));
+        // version numbers and unknown extensions
+        let (_, ext) = HartIsa::parse_isa_str("rv64i2p1m2p0_zicsr2p0_xfoo_zba").unwrap();
+        assert_eq!(
+            ext.iter().map(Extension::name).collect::<Vec<_>>(),
+            ["i", "m", "zicsr", "zmmul", "zba"]
+        );
+        // legacy `su` letters
+        let (_, ext) = HartIsa::parse_isa_str("rv64imafdvcsu").unwrap();
+        assert!(ext.contains(V) && ext.contains(C));
+        assert!(!ext.contains(Sstc));
+        assert!(HartIsa::parse_isa_str("rv128i").is_none());
+        assert!(HartIsa::parse_isa_str("x86").is_none());
+        assert_eq!(Extension::from_name("Zicboz"), Some(Zicboz));
+        assert_eq!(Extension::from_name("zfoo"), None);
+    }
+    #[test]
+    fn test_hart_isa_qemu() {
+        #[repr(align(8))]
+        struct AlignedBuffer {
+            pub data: [u8; RAW_DEVICE_TREE_QEMU.len()],
+        }
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE_QEMU],
+        });
+        aligned_data.data[..BUFFER_SIZE_QEMU].clone_from_slice(RAW_DEVICE_TREE_QEMU);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        let cpus = node.cpus().unwrap();
+        let isa = cpus.riscv_isa(0).unwrap();
+        assert_eq!(isa.xlen, 64);
+        assert!(isa.extensions.contains(Extension::H));
+        assert!(isa.extensions.contains(Extension::Zicboz));
+        assert!(isa.extensions.contains(Extension::Sstc));
+        assert_eq!(isa.cbom_block_size, Some(64));
+        assert_eq!(isa.cboz_block_size, Some(64));
+        // `riscv,isa` describes the same extensions
+        let isa_str = cpus
+            .by_id(0)
+            .unwrap()
+            .node()
+            .get_prop("riscv,isa")
+            .unwrap()
+            .deserialize::<StrSeq>();
+        let (_, extensions) = HartIsa::parse_isa_str(isa_str.iter().next().unwrap()).unwrap();
+        assert_eq!(extensions, isa.extensions);
+        assert_eq!(cpus.riscv_common_isa(), Some(isa));
+        assert!(cpus.riscv_isa(1).is_none());
+    }
+    #[test]
+    fn test_hart_isa_hifive() {
+        #[repr(align(8))]
+        struct AlignedBuffer {
+            pub data: [u8; RAW_DEVICE_TREE_HIFIVE.len()],
+        }
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE_HIFIVE],
+        });
+        aligned_data.data[..BUFFER_SIZE_HIFIVE].clone_from_slice(RAW_DEVICE_TREE_HIFIVE);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        let cpus = node.cpus().unwrap();
+        let monitor = cpus.riscv_isa(0).unwrap();
+        let application = cpus.riscv_isa(1).unwrap();
+        assert!(!monitor.extensions.contains(Extension::F));
+        assert!(application.extensions.is_superset(monitor.extensions));
+        assert_eq!(application.cbom_block_size, None);
+        // the monitor core has no floating point unit
+        let common = cpus.riscv_common_isa().unwrap();
+        assert_eq!(common.extensions, monitor.extensions);
+    }
+}
@@ -0,0 +1,426 @@
+use crate::buildin::{Node, PhandleArgs, PhandleArgsIter, Reg};
+
+/// Kind of a RISC-V platform interrupt controller.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IntcKind {
+    /// SiFive compatible core local interruptor, `riscv,clint0`.
+    Clint,
+    /// ACLINT machine-level software interrupt device, `riscv,aclint-mswi`.
+    AclintMswi,
+    /// ACLINT machine-level timer device, `riscv,aclint-mtimer`.
+    AclintMtimer,
+    /// ACLINT supervisor-level software interrupt device, `riscv,aclint-sswi`.
+    AclintSswi,
+    /// Platform-level interrupt controller, `riscv,plic0`.
+    Plic,
+    /// AIA advanced platform-level interrupt controller, `riscv,aplic`.
+    Aplic,
+    /// AIA incoming MSI controller, `riscv,imsics`.
+    Imsic,
+}
+
+/// Privilege mode an interrupt context delivers to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PrivilegeMode {
+    Machine,
+    Supervisor,
+}
+
+/// One entry of `interrupts-extended`, bound to a hart.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HartContext {
+    /// Hart ID of the processor owning the `riscv,cpu-intc`.
+    pub hart_id: u64,
+    /// Local interrupt number on the hart, e.g. 11 for machine external interrupts.
+    pub irq: u32,
+    /// Privilege mode of `irq`.
+    pub mode: PrivilegeMode,
+    /// Context index on the controller.
+    ///
+    /// For the PLIC and APLIC this is the position in `interrupts-extended`;
+    /// for other controllers it is the position of the hart among those listed.
+    pub index: usize,
+    /// Physical address of the registers of this context,
+    /// `None` if the controller has no address or the context lies beyond the address space.
+    pub base: Option<u64>,
+}
+
+/// A RISC-V platform interrupt controller node.
+pub struct RiscvIntc<'de> {
+    node: Node<'de>,
+    kind: IntcKind,
+    interrupts: Option<PhandleArgs<'de>>,
+}
+
+/// Iterator over the contexts of a controller.
+pub struct HartContextIter<'de, 'b> {
+    args: Option<PhandleArgsIter<'de, 'b>>,
+    position: usize,
+    slot: usize,
+    last: Option<u32>,
+    kind: IntcKind,
+    base: Option<u64>,
+    stride: u64,
+}
+
+const COMPATIBLES: &[(&str, IntcKind)] = &[
+    ("riscv,clint0", IntcKind::Clint),
+    ("sifive,clint0", IntcKind::Clint),
+    ("riscv,aclint-mswi", IntcKind::AclintMswi),
+    ("riscv,aclint-mtimer", IntcKind::AclintMtimer),
+    ("riscv,aclint-sswi", IntcKind::AclintSswi),
+    ("riscv,plic0", IntcKind::Plic),
+    ("sifive,plic-1.0.0", IntcKind::Plic),
+    ("riscv,aplic", IntcKind::Aplic),
+    ("riscv,imsics", IntcKind::Imsic),
+];
+
+const COMPATIBLE_TABLE: [&str; COMPATIBLES.len()] = {
+    let mut table = [""; COMPATIBLES.len()];
+    let mut i = 0;
+    while i < COMPATIBLES.len() {
+        table[i] = COMPATIBLES[i].0;
+        i += 1;
+    }
+    table
+};
+
+impl<'de> Node<'de> {
+    /// Lazily find every enabled RISC-V platform interrupt controller in the tree.
+    pub fn riscv_intcs(&self) -> impl Iterator<Item = RiscvIntc<'de>> + 'de {
+        self.find_compatible(&COMPATIBLE_TABLE)
+            .filter(|(_, node)| node.is_enabled())
+            .map(|(index, node)| RiscvIntc::new(node, COMPATIBLES[index].1))
+    }
+
+    /// Find the first enabled RISC-V platform interrupt controller of `kind`.
+    pub fn riscv_intc(&self, kind: IntcKind) -> Option<RiscvIntc<'de>> {
+        self.riscv_intcs().find(|intc| intc.kind == kind)
+    }
+}
+
+impl<'de> RiscvIntc<'de> {
+    /// Recognize a controller by its `compatible`.
+    pub fn from_node(node: Node<'de>) -> Option<Self> {
+        let index = node.compatible()?.matches(&COMPATIBLE_TABLE)?;
+        Some(Self::new(node, COMPATIBLES[index].1))
+    }
+
+    fn new(node: Node<'de>, kind: IntcKind) -> Self {
+        let interrupts = node.phandle_args("interrupts-extended", "#interrupt-cells");
+        Self {
+            node,
+            kind,
+            interrupts,
+        }
+    }
+
+    /// The controller node.
+    pub fn node(&self) -> &Node<'de> {
+        &self.node
+    }
+
+    /// Kind of the controller.
+    pub fn kind(&self) -> IntcKind {
+        self.kind
+    }
+
+    /// Physical address of the first `reg` entry.
+    pub fn base(&self) -> Option<u64> {
+        self.node.translated_reg_address()
+    }
+
+    /// Number of interrupt sources, from `riscv,ndev` or `riscv,num-sources`,
+    /// or the number of MSI identities of an IMSIC, from `riscv,num-ids`.
+    pub fn num_sources(&self) -> Option<u32> {
+        let name = match self.kind {
+            IntcKind::Plic => "riscv,ndev",
+            IntcKind::Aplic => "riscv,num-sources",
+            IntcKind::Imsic => "riscv,num-ids",
+            _ => return None,
+        };
+        self.node
+            .get_number(name)
+            .and_then(|value| u32::try_from(value).ok())
+    }
+
+    /// Iterate over the per-hart contexts listed in `interrupts-extended`.
+    ///
+    /// Entries not routed to a `riscv,cpu-intc`, or marked unused with `0xffffffff`
+    /// as the SiFive PLIC does for missing privilege modes, are skipped,
+    /// but still count towards the context index.
+    /// An APLIC in MSI mode has no contexts.
+    pub fn contexts<'b>(&'b self) -> HartContextIter<'de, 'b> {
+        let (base, stride) = match self.kind {
+            IntcKind::Clint => (self.base(), 0),
+            // registers are laid out per context at fixed offsets
+            IntcKind::AclintMswi | IntcKind::AclintSswi => (self.base(), 4),
+            IntcKind::AclintMtimer => (self.mtimecmp_base(), 8),
+            IntcKind::Plic => (
+                self.base().and_then(|base| base.checked_add(0x20_0000)),
+                0x1000,
+            ),
+            IntcKind::Aplic => (self.base().and_then(|base| base.checked_add(0x4000)), 0x20),
+            IntcKind::Imsic => {
+                let guest_bits = self.node.get_number("riscv,guest-index-bits").unwrap_or(0);
+                (
+                    self.base(),
+                    0x1000u64.checked_shl(guest_bits as u32).unwrap_or(0),
+                )
+            }
+        };
+        HartContextIter {
+            args: self.interrupts.as_ref().map(PhandleArgs::iter),
+            position: 0,
+            slot: 0,
+            last: None,
+            kind: self.kind,
+            base,
+            stride,
+        }
+    }
+
+    /// Find the context of `hart_id` in privilege `mode`.
+    pub fn context(&self, hart_id: u64, mode: PrivilegeMode) -> Option<HartContext> {
+        self.contexts()
+            .find(|context| context.hart_id == hart_id && context.mode == mode)
+    }
+
+    /// Address of the `mtimecmp` registers of an ACLINT MTIMER.
+    ///
+    /// With two `reg` entries, the first is `mtime` and the second is `mtimecmp`.
+    fn mtimecmp_base(&self) -> Option<u64> {
+        let reg = self.node.get_prop("reg")?.deserialize::<Reg>();
+        let mut regions = reg.iter();
+        let first = regions.next()?;
+        let region = regions.next().unwrap_or(first);
+        self.node.translate_address(region.0.start as u64)
+    }
+}
+
+impl core::fmt::Debug for RiscvIntc<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("RiscvIntc")
+            .field("kind", &self.kind)
+            .field("base", &self.base())
+            .finish()
+    }
+}
+
+impl Iterator for HartContextIter<'_, '_> {
+    type Item = HartContext;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let args = self.args.as_mut()?;
+        loop {
+            let arg = args.next()?;
+            let position = self.position;
+            self.position += 1;
+            // consecutive entries of the same hart share a slot
+            if self.last.is_some_and(|last| last != arg.phandle()) {
+                self.slot += 1;
+            }
+            self.last = Some(arg.phandle());
+
+            let provider = arg.provider();
+            let is_cpu_intc = provider
+                .compatible()
+                .is_some_and(|compatible| compatible.matches(&["riscv,cpu-intc"]).is_some());
+            let Some(irq) = arg.arg(0).filter(|&irq| irq != u32::MAX && is_cpu_intc) else {
+                continue;
+            };
+            let Some(hart_id) = provider.parent().and_then(|cpu| cpu.get_number("reg")) else {
+                continue;
+            };
+            let mode = match irq {
+                1 | 5 | 9 => PrivilegeMode::Supervisor,
+                3 | 7 | 11 => PrivilegeMode::Machine,
+                _ => continue,
+            };
+            let index = match self.kind {
+                IntcKind::Plic | IntcKind::Aplic => position,
+                _ => self.slot,
+            };
+            let base = self.base.and_then(|base| match (self.kind, irq) {
+                // `msip` and `mtimecmp` of a CLINT
+                (IntcKind::Clint, 7) => context_address(base.checked_add(0x4000)?, 8, index),
+                (IntcKind::Clint, _) => context_address(base, 4, index),
+                _ => context_address(base, self.stride, index),
+            });
+            return Some(HartContext {
+                hart_id,
+                irq,
+                mode,
+                index,
+                base,
+            });
+        }
+    }
+}
+
+/// Address of the registers of context `index`, `None` if it overflows.
+fn context_address(base: u64, stride: u64, index: usize) -> Option<u64> {
+    base.checked_add(stride.checked_mul(index as u64)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HartContext, IntcKind, PrivilegeMode};
+    use crate::buildin::Node;
+    use crate::{from_raw_mut, Dtb, DtbPtr};
+
+    const RAW_DEVICE_TREE_QEMU: &[u8] = include_bytes!("../../examples/qemu-virt.dtb");
+    const BUFFER_SIZE_QEMU: usize = RAW_DEVICE_TREE_QEMU.len();
+    const RAW_DEVICE_TREE_HIFIVE: &[u8] = include_bytes!("../../examples/hifive-unmatched-a00.dtb");
+    const BUFFER_SIZE_HIFIVE: usize = RAW_DEVICE_TREE_HIFIVE.len();
+    const RAW_DEVICE_TREE_AIA: &[u8] = include_bytes!("../../examples/riscv-aia.dtb");
+    const BUFFER_SIZE_AIA: usize = RAW_DEVICE_TREE_AIA.len();
+    #[test]
+    fn test_intc_qemu() {
+        #[repr(align(8))]
+        struct AlignedBuffer {
+            pub data: [u8; RAW_DEVICE_TREE_QEMU.len()],
+        }
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE_QEMU],
+        });
+        aligned_data.data[..BUFFER_SIZE_QEMU].clone_from_slice(RAW_DEVICE_TREE_QEMU);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        let kinds = node
+            .riscv_intcs()
+            .map(|intc| intc.kind())
+            .collect::<Vec<_>>();
+        assert_eq!(kinds, [IntcKind::Plic, IntcKind::Clint]);
+
+        let plic = node.riscv_intc(IntcKind::Plic).unwrap();
+        assert_eq!(plic.base(), Some(0xc00_0000));
+        assert_eq!(plic.num_sources(), Some(0x5f));
+        let contexts = plic.contexts().collect::<Vec<_>>();
+        assert_eq!(
+            contexts,
+            [
+                HartContext {
+                    hart_id: 0,
+                    irq: 11,
+                    mode: PrivilegeMode::Machine,
+                    index: 0,
+                    base: Some(0xc20_0000),
+                },
+                HartContext {
+                    hart_id: 0,
+                    irq: 9,
+                    mode: PrivilegeMode::Supervisor,
+                    index: 1,
+                    base: Some(0xc20_1000),
+                },
+            ]
+        );
+
+        let clint = node.riscv_intc(IntcKind::Clint).unwrap();
+        let bases = clint
+            .contexts()
+            .map(|context| (context.irq, context.index, context.base))
+            .collect::<Vec<_>>();
+        assert_eq!(bases, [(3, 0, Some(0x200_0000)), (7, 0, Some(0x200_4000))]);
+    }
+    #[test]
+    fn test_intc_hifive() {
+        #[repr(align(8))]
+        struct AlignedBuffer {
+            pub data: [u8; RAW_DEVICE_TREE_HIFIVE.len()],
+        }
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE_HIFIVE],
+        });
+        aligned_data.data[..BUFFER_SIZE_HIFIVE].clone_from_slice(RAW_DEVICE_TREE_HIFIVE);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        let plic = node.riscv_intc(IntcKind::Plic).unwrap();
+        assert_eq!(plic.num_sources(), Some(0x45));
+        // machine mode contexts are marked unused, the monitor core has none at all
+        let contexts = plic
+            .contexts()
+            .map(|context| (context.hart_id, context.mode, context.index))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            contexts,
+            [
+                (1, PrivilegeMode::Supervisor, 2),
+                (2, PrivilegeMode::Supervisor, 4),
+                (3, PrivilegeMode::Supervisor, 6),
+                (4, PrivilegeMode::Supervisor, 8),
+            ]
+        );
+        assert!(plic.context(0, PrivilegeMode::Machine).is_none());
+        let context = plic.context(4, PrivilegeMode::Supervisor).unwrap();
+        assert_eq!(context.base, Some(0xc00_0000 + 0x20_0000 + 8 * 0x1000));
+
+        let clint = node.riscv_intc(IntcKind::Clint).unwrap();
+        assert_eq!(clint.contexts().count(), 10);
+        let msip = clint.context(3, PrivilegeMode::Machine).unwrap();
+        assert_eq!((msip.irq, msip.index), (3, 3));
+        assert_eq!(msip.base, Some(0x200_000c));
+        let mtimecmp = clint.contexts().find(|c| c.hart_id == 4 && c.irq == 7);
+        assert_eq!(mtimecmp.unwrap().base, Some(0x200_4020));
+    }
+    #[test]
+    fn test_intc_aia() {
+        #[repr(align(8))]
+        struct AlignedBuffer {
+            pub data: [u8; RAW_DEVICE_TREE_AIA.len()],
+        }
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE_AIA],
+        });
+        aligned_data.data[..BUFFER_SIZE_AIA].clone_from_slice(RAW_DEVICE_TREE_AIA);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        let context_base = |kind, hart_id, mode| {
+            node.riscv_intc(kind)
+                .unwrap()
+                .context(hart_id, mode)
+                .and_then(|context| context.base)
+        };
+        use IntcKind::*;
+        use PrivilegeMode::*;
+        assert_eq!(context_base(AclintMswi, 1, Machine), Some(0x200_0004));
+        assert_eq!(context_base(AclintMtimer, 1, Machine), Some(0x200_4008));
+        assert_eq!(context_base(AclintSswi, 1, Supervisor), Some(0x2f0_0004));
+        assert_eq!(context_base(Imsic, 1, Machine), Some(0x2400_1000));
+        // an MSI mode APLIC has no direct contexts
+        let aplics = node
+            .riscv_intcs()
+            .filter(|intc| intc.kind() == Aplic)
+            .map(|intc| intc.contexts().count())
+            .collect::<Vec<_>>();
+        assert_eq!(aplics, [0, 2]);
+        let imsic_s = node
+            .riscv_intcs()
+            .find(|intc| intc.kind() == Imsic && intc.context(0, Supervisor).is_some())
+            .unwrap();
+        // one guest interrupt file besides the supervisor one
+        assert_eq!(
+            imsic_s.context(1, Supervisor).unwrap().base,
+            Some(0x2800_2000)
+        );
+        let aplic_m = node.find("/soc/aplic@c000000").unwrap();
+        let aplic_m = super::RiscvIntc::from_node(aplic_m).unwrap();
+        assert_eq!(aplic_m.num_sources(), Some(96));
+        assert_eq!(aplic_m.context(1, Machine).unwrap().base, Some(0xc00_4020));
+        // context addresses beyond the address space
+        let plic = node.riscv_intc(Plic).unwrap();
+        assert_eq!(plic.base(), Some(0xffff_ffff_fff0_0000));
+        assert_eq!(plic.contexts().count(), 2);
+        assert!(plic.contexts().all(|context| context.base.is_none()));
+    }
+}
@@ -0,0 +1,262 @@
+use crate::buildin::{Node, StrSeq};
+
+/// Parity of a serial line.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Parity {
+    #[default]
+    None,
+    Odd,
+    Even,
+}
+
+/// Serial line configuration, the `:options` suffix of `stdout-path`.
+///
+/// The options have the form `<baud>{<parity>{<bits>{<flow>}}}`, for example `115200n8r`,
+/// where parity is one of `n`, `o` or `e`, and `r` enables RTS/CTS flow control.
+/// Parts that are not given keep their default values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SerialOptions {
+    pub baud: Option<u32>,
+    pub parity: Parity,
+    pub data_bits: u8,
+    pub flow_control: bool,
+}
+
+impl Default for SerialOptions {
+    fn default() -> Self {
+        Self {
+            baud: None,
+            parity: Parity::None,
+            data_bits: 8,
+            flow_control: false,
+        }
+    }
+}
+
+impl SerialOptions {
+    /// Parse serial options, returns `None` if they are malformed.
+    pub fn parse(options: &str) -> Option<Self> {
+        let mut result = Self::default();
+        let digits = options.bytes().take_while(u8::is_ascii_digit).count();
+        let (baud, rest) = options.split_at(digits);
+        if !baud.is_empty() {
+            result.baud = Some(baud.parse().ok()?);
+        }
+        let mut rest = rest.bytes();
+        let mut next = rest.next();
+        if let Some(parity) = next {
+            result.parity = match parity {
+                b'n' => Parity::None,
+                b'o' => Parity::Odd,
+                b'e' => Parity::Even,
+                _ => return None,
+            };
+            next = rest.next();
+        }
+        if let Some(bits @ b'5'..=b'8') = next {
+            result.data_bits = bits - b'0';
+            next = rest.next();
+        }
+        if next == Some(b'r') {
+            result.flow_control = true;
+            next = rest.next();
+        }
+        next.is_none().then_some(result)
+    }
+}
+
+/// A memory mapped serial port used as the console.
+///
+/// Carries everything an early console driver needs to start printing.
+pub struct SerialConsole<'de> {
+    node: Node<'de>,
+    compatible: Option<&'de str>,
+    base: Option<u64>,
+    reg_shift: u32,
+    reg_io_width: u32,
+    clock_frequency: Option<u64>,
+    options: SerialOptions,
+}
+
+impl<'de> SerialConsole<'de> {
+    /// Read the serial port description from its node.
+    pub fn from_node(node: Node<'de>, options: SerialOptions) -> Self {
+        let compatible = node
+            .get_prop("compatible")
+            .and_then(|prop| prop.deserialize::<StrSeq>().iter().next());
+        let u32_prop = |name, default| {
+            node.get_number(name)
+                .and_then(|value| u32::try_from(value).ok())
+                .unwrap_or(default)
+        };
+        Self {
+            compatible,
+            base: node.translated_reg_address(),
+            reg_shift: u32_prop("reg-shift", 0),
+            reg_io_width: u32_prop("reg-io-width", 1),
+            clock_frequency: node.get_number("clock-frequency"),
+            options,
+            node,
+        }
+    }
+
+    /// The serial port node.
+    pub fn node(&self) -> &Node<'de> {
+        &self.node
+    }
+
+    /// The most specific entry of `compatible`.
+    pub fn compatible(&self) -> Option<&'de str> {
+        self.compatible
+    }
+
+    /// Physical address of the registers, translated through the bus `ranges`.
+    pub fn base(&self) -> Option<u64> {
+        self.base
+    }
+
+    /// Register address shift, from `reg-shift`, defaults to 0.
+    pub fn reg_shift(&self) -> u32 {
+        self.reg_shift
+    }
+
+    /// Register access width in bytes, from `reg-io-width`, defaults to 1.
+    pub fn reg_io_width(&self) -> u32 {
+        self.reg_io_width
+    }
+
+    /// Input clock frequency in Hz, from `clock-frequency`.
+    pub fn clock_frequency(&self) -> Option<u64> {
+        self.clock_frequency
+    }
+
+    /// Line configuration given in `stdout-path`.
+    pub fn options(&self) -> SerialOptions {
+        self.options
+    }
+}
+
+impl<'de> Node<'de> {
+    /// Resolve `/chosen/stdout-path` to the console serial port.
+    ///
+    /// The path may start with an alias. Malformed options are replaced by the defaults.
+    pub fn chosen_stdout(&self) -> Option<SerialConsole<'de>> {
+        let path = self
+            .chosen()?
+            .get_prop("stdout-path")?
+            .deserialize::<StrSeq>()
+            .iter()
+            .next()?;
+        let options = match path.split_once(':') {
+            Some((_, options)) => SerialOptions::parse(options).unwrap_or_default(),
+            None => SerialOptions::default(),
+        };
+        Some(SerialConsole::from_node(self.find(path)?, options))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Parity, SerialOptions};
+    use crate::{buildin::Node, from_raw_mut, Dtb, DtbPtr};
+
+    const RAW_DEVICE_TREE: &[u8] = include_bytes!("../../examples/chosen.dtb");
+    const BUFFER_SIZE: usize = RAW_DEVICE_TREE.len();
+    const RAW_DEVICE_TREE_BL808: &[u8] = include_bytes!("../../examples/bl808.dtb");
+    const BUFFER_SIZE_BL808: usize = RAW_DEVICE_TREE_BL808.len();
+    const RAW_DEVICE_TREE_QEMU: &[u8] = include_bytes!("../../examples/qemu-virt.dtb");
+    const BUFFER_SIZE_QEMU: usize = RAW_DEVICE_TREE_QEMU.len();
+    #[test]
+    fn test_serial_options() {
+        let options = SerialOptions::parse("115200n8r").unwrap();
+        assert_eq!(
+            options,
+            SerialOptions {
+                baud: Some(115200),
+                parity: Parity::None,
+                data_bits: 8,
+                flow_control: true,
+            }
+        );
+        let options = SerialOptions::parse("9600e7").unwrap();
+        assert_eq!(options.baud, Some(9600));
+        assert_eq!((options.parity, options.data_bits), (Parity::Even, 7));
+        assert!(!options.flow_control);
+        assert_eq!(SerialOptions::parse("1500000").unwrap().baud, Some(1500000));
+        assert_eq!(SerialOptions::parse(""), Some(SerialOptions::default()));
+        assert_eq!(SerialOptions::parse("115200x8"), None);
+        assert_eq!(SerialOptions::parse("115200n9"), None);
+        assert_eq!(SerialOptions::parse("99999999999"), None);
+    }
+    #[test]
+    fn test_chosen_stdout() {
+        #[repr(align(8))]
+        struct AlignedBuffer {
+            pub data: [u8; RAW_DEVICE_TREE.len()],
+        }
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE],
+        });
+        aligned_data.data[..BUFFER_SIZE].clone_from_slice(RAW_DEVICE_TREE);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        let console = node.chosen_stdout().unwrap();
+        assert_eq!(console.node().path(), "/soc/serial@0");
+        assert_eq!(console.compatible(), Some("snps,dw-apb-uart"));
+        assert_eq!(console.base(), Some(0x1000_0000));
+        assert_eq!((console.reg_shift(), console.reg_io_width()), (2, 4));
+        assert_eq!(console.clock_frequency(), Some(3686400));
+        assert_eq!(console.options().baud, Some(115200));
+        assert!(console.options().flow_control);
+    }
+    #[test]
+    fn test_chosen_stdout_bl808() {
+        #[repr(align(8))]
+        struct AlignedBuffer {
+            pub data: [u8; RAW_DEVICE_TREE_BL808.len()],
+        }
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE_BL808],
+        });
+        aligned_data.data[..BUFFER_SIZE_BL808].clone_from_slice(RAW_DEVICE_TREE_BL808);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        let console = node.chosen_stdout().unwrap();
+        assert_eq!(console.node().path(), "/bus@30000000/serial@30002000");
+        assert_eq!(console.compatible(), Some("bflb,bl808-uart"));
+        assert_eq!(console.base(), Some(0x3000_2000));
+        assert_eq!((console.reg_shift(), console.reg_io_width()), (0, 1));
+        assert_eq!(console.clock_frequency(), None);
+        assert_eq!(
+            (console.options().baud, console.options().data_bits),
+            (Some(115200), 8)
+        );
+    }
+    #[test]
+    fn test_chosen_stdout_qemu() {
+        #[repr(align(8))]
+        struct AlignedBuffer {
+            pub data: [u8; RAW_DEVICE_TREE_QEMU.len()],
+        }
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE_QEMU],
+        });
+        aligned_data.data[..BUFFER_SIZE_QEMU].clone_from_slice(RAW_DEVICE_TREE_QEMU);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        let console = node.chosen_stdout().unwrap();
+        assert_eq!(console.compatible(), Some("ns16550a"));
+        assert_eq!(console.base(), Some(0x1000_0000));
+        assert_eq!(console.clock_frequency(), Some(0x384000));
+        assert_eq!(console.options(), SerialOptions::default());
+    }
+}
@@ -0,0 +1,94 @@
+use crate::buildin::{Node, StrSeq};
+
+/// Iterator over the names in `/__symbols__` or `/aliases` that refer to one node.
+pub struct Labels<'de> {
+    table: Option<Node<'de>>,
+    node: Node<'de>,
+    skip: usize,
+}
+
+impl<'de> Node<'de> {
+    /// Try to get a node by a label, using the `/__symbols__` node emitted by `dtc -@`.
+    pub fn find_by_label(&self, label: &str) -> Option<Node<'de>> {
+        let root = self.root();
+        let path = root
+            .raw_find("/__symbols__")?
+            .get_prop(label)?
+            .deserialize::<StrSeq>()
+            .iter()
+            .next()?;
+        root.raw_find(path)
+    }
+
+    /// Get every label in `/__symbols__` that refers to this node.
+    pub fn labels(&self) -> Labels<'de> {
+        Labels {
+            table: self.root().raw_find("/__symbols__"),
+            node: self.clone(),
+            skip: 0,
+        }
+    }
+
+    /// Get every alias in `/aliases` that refers to this node.
+    pub fn aliases(&self) -> Labels<'de> {
+        Labels {
+            table: self.root().raw_find("/aliases"),
+            node: self.clone(),
+            skip: 0,
+        }
+    }
+}
+
+impl<'de> Iterator for Labels<'de> {
+    type Item = &'de str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let table = self.table.as_ref()?;
+        for prop in table.props().skip(self.skip) {
+            self.skip += 1;
+            let path = prop.deserialize::<StrSeq>().iter().next();
+            if path.is_some_and(|path| self.node.path() == path) {
+                return Some(prop.get_name());
+            }
+        }
+        self.table = None;
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{buildin::Node, from_raw_mut, Dtb, DtbPtr};
+
+    const RAW_DEVICE_TREE: &[u8] = include_bytes!("../../examples/bl808.dtb");
+    const BUFFER_SIZE: usize = RAW_DEVICE_TREE.len();
+    #[test]
+    fn test_labels() {
+        #[repr(align(8))]
+        struct AlignedBuffer {
+            pub data: [u8; RAW_DEVICE_TREE.len()],
+        }
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE],
+        });
+        aligned_data.data[..BUFFER_SIZE].clone_from_slice(RAW_DEVICE_TREE);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        let uart3 = node.find_by_label("uart3").unwrap();
+        assert!(uart3.path() == "/bus@30000000/serial@30002000");
+        assert!(node.find_by_label("uart4").is_none());
+        assert_eq!(uart3.labels().collect::<Vec<_>>(), ["uart3"]);
+        assert_eq!(uart3.aliases().collect::<Vec<_>>(), ["serial3"]);
+
+        let intc = node.find_by_label("cpu0_intc").unwrap();
+        assert_eq!(intc.labels().next(), Some("cpu0_intc"));
+        assert_eq!(intc.aliases().next(), None);
+        assert_eq!(node.find("/chosen").unwrap().labels().next(), None);
+        // labels are global, lookup from any node gives the same result
+        let uart3 = intc.find_by_label("uart3").unwrap();
+        assert!(uart3.path() == "/bus@30000000/serial@30002000");
+    }
+}
@@ -14,6 +14,19 @@ impl<'a> Compatible<'a> {
             remaining: self.data,
         }
     }
+
+    /// Match this device against a driver table.
+    ///
+    /// Compatible strings are listed from the most specific to the most general,
+    /// so the first string found in `table` is the best match.
+    /// Returns the index into `table` of that string.
+    pub fn matches(&self, table: &[&str]) -> Option<usize> {
+        self.iter().find_map(|compatible| {
+            table
+                .iter()
+                .position(|driver| driver.as_bytes() == compatible)
+        })
+    }
 }
 
 impl<'de: 'a, 'a> Deserialize<'de> for Compatible<'a> {
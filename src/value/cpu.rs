@@ -1,4 +1,274 @@
-// /// ISA independent processor description.
-// pub struct CpuMut<'a> {
-//     memory: &'a mut [u8],
-// }
+use crate::buildin::{CellArray, Node, NodeIter, Status, StrSeq};
+
+/// The `/cpus` node, describing all processors of the system.
+#[derive(Clone)]
+pub struct Cpus<'de> {
+    node: Node<'de>,
+    address_cells: usize,
+}
+
+/// ISA independent processor description, a `/cpus/cpu@N` node.
+#[derive(Clone)]
+pub struct Cpu<'de> {
+    node: Node<'de>,
+    id: u64,
+}
+
+/// Iterator over processors, in the order they appear in the tree.
+pub struct CpuIter<'de, 'b> {
+    nodes: NodeIter<'de, 'b>,
+    address_cells: usize,
+}
+
+/// Position of a processor in `/cpus/cpu-map`.
+///
+/// A level that is not present in the map is reported as 0;
+/// with nested clusters, `cluster` is the number of the innermost one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CpuTopology {
+    pub socket: u32,
+    pub cluster: u32,
+    pub core: u32,
+    pub thread: Option<u32>,
+}
+
+impl<'de> Node<'de> {
+    /// Get the `/cpus` node.
+    pub fn cpus(&self) -> Option<Cpus<'de>> {
+        Cpus::from_node(self.find("/cpus")?)
+    }
+}
+
+impl<'de> Cpus<'de> {
+    /// Read processors from a `/cpus` node.
+    pub fn from_node(node: Node<'de>) -> Option<Self> {
+        let address_cells = node.get_number("#address-cells").unwrap_or(2) as usize;
+        (1..=2).contains(&address_cells).then_some(Self {
+            node,
+            address_cells,
+        })
+    }
+
+    /// The `/cpus` node.
+    pub fn node(&self) -> &Node<'de> {
+        &self.node
+    }
+
+    /// Frequency of the timebase in Hz, from `timebase-frequency`.
+    pub fn timebase_frequency(&self) -> Option<u64> {
+        self.node.get_number("timebase-frequency")
+    }
+
+    /// Iterate over all processors.
+    ///
+    /// A `cpu` node without a valid `reg` is skipped.
+    pub fn iter<'b>(&'b self) -> CpuIter<'de, 'b> {
+        CpuIter {
+            nodes: self.node.nodes(),
+            address_cells: self.address_cells,
+        }
+    }
+
+    /// Iterate over processors that are enabled.
+    pub fn iter_enabled<'b>(&'b self) -> impl Iterator<Item = Cpu<'de>> + 'b {
+        self.iter().filter(Cpu::is_enabled)
+    }
+
+    /// Find the processor whose hart ID or MPIDR is `id`.
+    pub fn by_id(&self, id: u64) -> Option<Cpu<'de>> {
+        self.iter().find(|cpu| cpu.id == id)
+    }
+
+    /// Find the position of a processor in `/cpus/cpu-map`.
+    pub fn topology(&self, cpu: &Cpu) -> Option<CpuTopology> {
+        let phandle = cpu.node.phandle()?;
+        let map = self
+            .node
+            .nodes()
+            .find(|item| item.get_full_name() == "cpu-map")?;
+        find_in_map(&map.to_node(), phandle, CpuTopology::default())
+    }
+}
+
+impl<'de> Cpu<'de> {
+    /// Read a processor from its node, `address_cells` is that of `/cpus`.
+    fn from_node(node: Node<'de>, address_cells: usize) -> Option<Self> {
+        let data = node.get_prop("reg")?.deserialize::<&[u8]>();
+        let [id, _] = CellArray::new(data)
+            .entries([address_cells, 0])
+            .ok()?
+            .next()?;
+        Some(Self { node, id })
+    }
+
+    /// The processor node.
+    pub fn node(&self) -> &Node<'de> {
+        &self.node
+    }
+
+    /// Processor ID from the first entry of `reg`,
+    /// which is the hart ID on RISC-V and the affinity bits of MPIDR on ARM.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Device status of the processor.
+    pub fn status(&self) -> Option<Status<'de>> {
+        self.node.status()
+    }
+
+    /// Whether the processor can be used.
+    pub fn is_enabled(&self) -> bool {
+        self.node.is_enabled()
+    }
+
+    /// How the processor is brought up, for example `"psci"` or `"spin-table"`.
+    pub fn enable_method(&self) -> Option<&'de str> {
+        self.str_prop("enable-method")
+    }
+
+    /// Type of the MMU, for example `"riscv,sv39"`.
+    pub fn mmu_type(&self) -> Option<&'de str> {
+        self.str_prop("mmu-type")
+    }
+
+    /// Address polled by a processor started with the `spin-table` method.
+    pub fn cpu_release_addr(&self) -> Option<u64> {
+        self.node.get_number("cpu-release-addr")
+    }
+
+    fn str_prop(&self, name: &str) -> Option<&'de str> {
+        self.node
+            .get_prop(name)?
+            .deserialize::<StrSeq>()
+            .iter()
+            .next()
+    }
+}
+
+impl core::fmt::Debug for Cpu<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Cpu")
+            .field("id", &self.id)
+            .field("status", &self.status())
+            .field("enable_method", &self.enable_method())
+            .field("mmu_type", &self.mmu_type())
+            .finish()
+    }
+}
+
+impl<'de> Iterator for CpuIter<'de, '_> {
+    type Item = Cpu<'de>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let address_cells = self.address_cells;
+        self.nodes.find_map(|item| {
+            // only `cpu` and `cpu@N` children describe processors
+            if item.get_parsed_name().0 != "cpu" {
+                return None;
+            }
+            Cpu::from_node(item.to_node(), address_cells)
+        })
+    }
+}
+
+/// Search `node` of the cpu map for the leaf referring to `phandle`.
+fn find_in_map(node: &Node, phandle: u32, at: CpuTopology) -> Option<CpuTopology> {
+    if node.get_number("cpu") == Some(phandle as u64) {
+        return Some(at);
+    }
+    node.nodes().find_map(|child| {
+        let name = child.get_full_name();
+        let number = |prefix: &str| name.strip_prefix(prefix)?.parse::<u32>().ok();
+        let mut at = at;
+        if let Some(n) = number("socket") {
+            at.socket = n;
+        } else if let Some(n) = number("cluster") {
+            at.cluster = n;
+        } else if let Some(n) = number("core") {
+            at.core = n;
+        } else {
+            at.thread = Some(number("thread")?);
+        }
+        find_in_map(&child.to_node(), phandle, at)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CpuTopology;
+    use crate::buildin::{Node, Status};
+    use crate::{from_raw_mut, Dtb, DtbPtr};
+
+    const RAW_DEVICE_TREE: &[u8] = include_bytes!("../../examples/arm64.dtb");
+    const BUFFER_SIZE: usize = RAW_DEVICE_TREE.len();
+    const RAW_DEVICE_TREE_HIFIVE: &[u8] = include_bytes!("../../examples/hifive-unmatched-a00.dtb");
+    const BUFFER_SIZE_HIFIVE: usize = RAW_DEVICE_TREE_HIFIVE.len();
+    #[test]
+    fn test_cpus() {
+        #[repr(align(8))]
+        struct AlignedBuffer {
+            pub data: [u8; RAW_DEVICE_TREE.len()],
+        }
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE],
+        });
+        aligned_data.data[..BUFFER_SIZE].clone_from_slice(RAW_DEVICE_TREE);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        let cpus = node.cpus().unwrap();
+        let ids = cpus.iter().map(|cpu| cpu.id()).collect::<Vec<_>>();
+        assert_eq!(ids, [0x0, 0x1, 0x100, 0x100_0000_0101]);
+        assert_eq!(cpus.iter_enabled().count(), 3);
+        assert_eq!(cpus.timebase_frequency(), None);
+
+        let cpu = cpus.by_id(0x100).unwrap();
+        assert_eq!(cpu.enable_method(), Some("spin-table"));
+        assert_eq!(cpu.cpu_release_addr(), Some(0x8000_fff8));
+        assert_eq!(
+            cpus.topology(&cpu),
+            Some(CpuTopology {
+                socket: 0,
+                cluster: 1,
+                core: 0,
+                thread: None,
+            })
+        );
+        let cpu = cpus.by_id(0x1).unwrap();
+        assert_eq!(cpu.enable_method(), Some("psci"));
+        assert_eq!(cpu.cpu_release_addr(), None);
+        assert_eq!(cpus.topology(&cpu).unwrap().thread, Some(1));
+        let cpu = cpus.by_id(0x100_0000_0101).unwrap();
+        assert_eq!(cpu.status(), Some(Status::Disabled));
+        assert_eq!(cpus.topology(&cpu).unwrap().core, 1);
+    }
+    #[test]
+    fn test_cpus_hifive() {
+        #[repr(align(8))]
+        struct AlignedBuffer {
+            pub data: [u8; RAW_DEVICE_TREE_HIFIVE.len()],
+        }
+        let mut aligned_data: Box<AlignedBuffer> = Box::new(AlignedBuffer {
+            data: [0; BUFFER_SIZE_HIFIVE],
+        });
+        aligned_data.data[..BUFFER_SIZE_HIFIVE].clone_from_slice(RAW_DEVICE_TREE_HIFIVE);
+        let mut slice = aligned_data.data.to_vec();
+        let ptr = DtbPtr::from_raw(slice.as_mut_ptr()).unwrap();
+        let dtb = Dtb::from(ptr).share();
+
+        let node: Node = from_raw_mut(&dtb).unwrap();
+        let cpus = node.cpus().unwrap();
+        assert_eq!(cpus.timebase_frequency(), Some(1_000_000));
+        let ids = cpus.iter().map(|cpu| cpu.id()).collect::<Vec<_>>();
+        assert_eq!(ids, [0, 1, 2, 3, 4]);
+        // the monitor core has no MMU
+        assert_eq!(cpus.by_id(0).unwrap().mmu_type(), None);
+        assert_eq!(cpus.by_id(1).unwrap().mmu_type(), Some("riscv,sv39"));
+        assert!(cpus.by_id(5).is_none());
+        // no cpu-map in this tree
+        assert_eq!(cpus.topology(&cpus.by_id(1).unwrap()), None);
+    }
+}